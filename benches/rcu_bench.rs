@@ -0,0 +1,192 @@
+//! Benchmarks of the `get` hot path and of `update`.
+//!
+//! Run with `cargo bench`. Each benchmark reports its throughput in
+//! operations per second.
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Barrier, Mutex};
+use std::thread::scope;
+use std::time::{Duration, Instant};
+
+use rcurs::{Rcu, RcuString};
+
+/// How long each benchmark runs.
+const DURATION: Duration = Duration::from_millis(500);
+
+/// The reader counts of the concurrent benchmarks.
+const READERS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
+/// The writer counts of the string benchmarks.
+const WRITERS: [usize; 3] = [1, 2, 4];
+
+/// The length at which the string benchmarks start over, so that the
+/// cost of an append does not depend on how long the benchmark ran.
+const STRING_MAX: usize = 1024;
+
+/// A value that highlights the per-object overhead.
+type Small = u64;
+
+/// A value of a more realistic size.
+#[derive(Clone)]
+struct Large(#[allow(dead_code)] [u8; 128]);
+
+impl Default for Large {
+	fn default() -> Self {
+		Self([0; 128])
+	}
+}
+
+fn report(name: &str, ops: u64, elapsed: Duration) {
+	#[allow(clippy::cast_precision_loss)]
+	let rate = ops as f64 / elapsed.as_secs_f64();
+	println!("{name:<40} {rate:>16.0} ops/s");
+}
+
+/// Call `f` in a loop for [`DURATION`] and return the number of calls.
+fn run(mut f: impl FnMut()) -> u64 {
+	let start = Instant::now();
+	let mut ops = 0;
+
+	while start.elapsed() < DURATION {
+		for _ in 0..1000 {
+			f();
+		}
+		ops += 1000;
+	}
+
+	ops
+}
+
+fn bench_get<T: Default>(name: &str) {
+	let rcu = Rcu::new(T::default());
+
+	let start = Instant::now();
+	let ops = run(|| {
+		black_box(&*rcu.get());
+	});
+	report(&format!("get/{name}"), ops, start.elapsed());
+}
+
+fn bench_get_concurrent<T: Default + Send + Sync>(name: &str) {
+	for readers in READERS {
+		let rcu = Rcu::new(T::default());
+		let ops = AtomicU64::new(0);
+		let start = Barrier::new(readers + 1);
+
+		let t = Instant::now();
+		scope(|scope| {
+			for _ in 0..readers {
+				scope.spawn(|| {
+					start.wait();
+					let n = run(|| {
+						black_box(&*rcu.get());
+					});
+					ops.fetch_add(n, Ordering::Relaxed);
+				});
+			}
+
+			start.wait();
+		});
+
+		report(
+			&format!("get/{name}/{readers} readers"),
+			ops.into_inner(),
+			t.elapsed(),
+		);
+	}
+}
+
+fn bench_update<T: Default + Send + Sync>(
+	name: &str,
+	readers: usize,
+) {
+	let rcu = Rcu::new(T::default());
+	let done = AtomicBool::new(false);
+	let start = Barrier::new(readers + 1);
+
+	scope(|scope| {
+		for _ in 0..readers {
+			scope.spawn(|| {
+				start.wait();
+				while !done.load(Ordering::Relaxed) {
+					black_box(&*rcu.get());
+				}
+			});
+		}
+
+		start.wait();
+		let t = Instant::now();
+		let ops = run(|| rcu.update(T::default()));
+		report(
+			&format!("update/{name}/{readers} readers"),
+			ops,
+			t.elapsed(),
+		);
+
+		done.store(true, Ordering::Relaxed);
+	});
+}
+
+/// Run `append` from `writers` threads at once and report the total
+/// throughput.
+fn bench_append(
+	name: &str,
+	writers: usize,
+	append: impl Fn() + Sync,
+) {
+	let ops = AtomicU64::new(0);
+	let start = Barrier::new(writers);
+
+	let t = Instant::now();
+	scope(|scope| {
+		for _ in 0..writers {
+			scope.spawn(|| {
+				start.wait();
+				ops.fetch_add(run(&append), Ordering::Relaxed);
+			});
+		}
+	});
+
+	report(
+		&format!("push_str/{name}/{writers} writers"),
+		ops.into_inner(),
+		t.elapsed(),
+	);
+}
+
+fn bench_push_str() {
+	for writers in WRITERS {
+		let rcu = RcuString::default();
+		bench_append("rcu", writers, || {
+			if rcu.get().len() >= STRING_MAX {
+				rcu.clear();
+			}
+			rcu.push_str("ab");
+		});
+
+		let mutex = Mutex::new(String::new());
+		bench_append("mutex", writers, || {
+			let mut s = mutex.lock().unwrap();
+			if s.len() >= STRING_MAX {
+				s.clear();
+			}
+			s.push_str("ab");
+		});
+	}
+}
+
+fn main() {
+	bench_get::<Small>("small");
+	bench_get::<Large>("large");
+
+	bench_get_concurrent::<Small>("small");
+	bench_get_concurrent::<Large>("large");
+
+	bench_update::<Small>("small", 0);
+	bench_update::<Large>("large", 0);
+	bench_update::<Small>("small", 4);
+	bench_update::<Large>("large", 4);
+
+	bench_push_str();
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::Reclaim;
+
+impl<T, R: Reclaim> Rcu<Arc<T>, R> {
+	/// Get the [`Arc`] inside the [`Rcu`].
+	///
+	/// See: [`Rcu::get`].
+	pub fn get_arc(&self) -> Guard<'_, Arc<T>> {
+		self.get()
+	}
+
+	/// Get a clone of the [`Arc`] inside the [`Rcu`].
+	///
+	/// Unlike a guard, the returned [`Arc`] does not borrow the [`Rcu`]
+	/// and can be kept around for as long as needed.
+	pub fn load_arc(&self) -> Arc<T> {
+		Arc::clone(&self.get())
+	}
+
+	/// Update the value inside the [`Rcu`] to `new`.
+	///
+	/// Only the [`Arc`] is moved into the [`Rcu`], never the value it
+	/// points to. This avoids copying large values that are already
+	/// behind an [`Arc`].
+	///
+	/// See: [`Rcu::update`].
+	pub fn update_arc(&self, new: Arc<T>) {
+		self.update(new);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::ArcRcu;
+
+	#[test]
+	fn test_arc() {
+		let rcu = ArcRcu::new(Arc::new([0u8; 1024]));
+		let old = rcu.load_arc();
+
+		let new = Arc::new([1; 1024]);
+		rcu.update_arc(Arc::clone(&new));
+
+		assert_eq!(old[0], 0);
+		assert!(Arc::ptr_eq(&rcu.get_arc(), &new));
+	}
+}
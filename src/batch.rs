@@ -0,0 +1,158 @@
+use core::{fmt, mem, ptr};
+
+use alloc::vec::Vec;
+
+use crate::rcu::{OldGuard, Rcu};
+use crate::reclaim::{self, Inner, Reclaim, RefCounting};
+
+/// A set of values that are ready to be swapped into an [`Rcu`].
+///
+/// All values are allocated when the [`BatchUpdate`] is created, so that
+/// [`install`]ing one of them later does not allocate. This is useful when
+/// the next value has to be picked and installed with predictable
+/// latency. The values that are not installed are dropped afterwards.
+///
+/// See: [`Rcu::batch_update`].
+///
+/// [`install`]: Self::install
+pub struct BatchUpdate<
+	'a,
+	T,
+	const N: usize,
+	R: Reclaim = RefCounting,
+> {
+	rcu: &'a Rcu<T, R>,
+	/// Null once installed.
+	nodes: [*mut Inner<T>; N],
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Prepare `values` to be swapped into the [`Rcu`].
+	///
+	/// See: [`BatchUpdate`].
+	pub fn batch_update<const N: usize>(
+		&self,
+		values: [T; N],
+	) -> BatchUpdate<'_, T, N, R> {
+		BatchUpdate { rcu: self, nodes: values.map(reclaim::alloc) }
+	}
+
+	/// Update the value inside the [`Rcu`] to each of `values` in turn.
+	///
+	/// The final value is the last one of `values`. Readers might see any
+	/// of the values in between, or none of them.
+	///
+	/// Each value is allocated right before it is installed. See
+	/// [`update_batch_eager`] to allocate all of them up front.
+	///
+	/// [`update_batch_eager`]: Self::update_batch_eager
+	pub fn update_batch<I>(&self, values: I)
+	where
+		I: IntoIterator<Item = T>,
+	{
+		for x in values {
+			self.update(x);
+		}
+	}
+
+	/// Same as [`update_batch`], but all values are allocated before the
+	/// first one is installed.
+	///
+	/// This keeps the allocator out of the updates, at the cost of having
+	/// all values in memory at once.
+	///
+	/// [`update_batch`]: Self::update_batch
+	pub fn update_batch_eager<I>(&self, values: I)
+	where
+		I: IntoIterator<Item = T>,
+	{
+		let nodes: Vec<_> =
+			values.into_iter().map(reclaim::alloc).collect();
+
+		for node in nodes {
+			drop(unsafe { self.install(node) });
+		}
+	}
+}
+
+impl<'a, T, const N: usize, R: Reclaim> BatchUpdate<'a, T, N, R> {
+	/// Get the prepared value at `index`.
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<&T> {
+		self.nodes.get(index).map(|x| unsafe { &*Inner::data(*x) })
+	}
+
+	/// Install the value at `index` and return a guard to the old one.
+	///
+	/// The swap itself does not allocate. The other values are dropped
+	/// after it.
+	///
+	/// # Panics
+	///
+	/// If `index` is out of bounds.
+	#[must_use]
+	pub fn install(mut self, index: usize) -> OldGuard<'a, T> {
+		let node =
+			mem::replace(&mut self.nodes[index], ptr::null_mut());
+		unsafe { self.rcu.install(node) }
+	}
+}
+
+impl<T: fmt::Debug, const N: usize, R: Reclaim> fmt::Debug
+	for BatchUpdate<'_, T, N, R>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_list()
+			.entries((0..N).filter_map(|i| self.get(i)))
+			.finish()
+	}
+}
+
+impl<T, const N: usize, R: Reclaim> Drop
+	for BatchUpdate<'_, T, N, R>
+{
+	fn drop(&mut self) {
+		for node in self.nodes {
+			if !node.is_null() {
+				drop(unsafe { reclaim::into_data(node) });
+			}
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_batch_update() {
+		let rcu = Rcu::new(0);
+
+		let batch = rcu.batch_update([1, 2, 3]);
+		assert_eq!(batch.get(1), Some(&2));
+		assert_eq!(batch.get(3), None);
+
+		let old = batch.install(1);
+		assert_eq!(*old, 0);
+		assert_eq!(*rcu.get(), 2);
+
+		drop(rcu.batch_update([4, 5]));
+		assert_eq!(*rcu.get(), 2);
+	}
+
+	#[test]
+	fn test_update_batch() {
+		let rcu = Rcu::new(0);
+
+		rcu.update_batch(1..=3);
+		assert_eq!(*rcu.get(), 3);
+		assert_eq!(rcu.generation(), 3);
+
+		rcu.update_batch_eager(4..=6);
+		assert_eq!(*rcu.get(), 6);
+		assert_eq!(rcu.generation(), 6);
+
+		rcu.update_batch_eager(None);
+		assert_eq!(rcu.generation(), 6);
+	}
+}
@@ -0,0 +1,125 @@
+use core::{cell::RefCell, fmt, ops::Deref};
+
+use alloc::rc::Rc;
+
+/// A single-threaded [`Rcu`].
+///
+/// This has the same semantics as an [`Rcu`]: a [`CellGuard`] always sees
+/// the value that was current when it was created, even if the value is
+/// updated while it is alive. But it uses plain [`Rc`]s instead of atomics,
+/// so [`RcuCell`] cannot be shared between threads. This makes it a
+/// better fit for single-threaded event loops and for targets without
+/// atomics.
+///
+/// [`Rcu`]: crate::Rcu
+pub struct RcuCell<T> {
+	current: RefCell<Rc<T>>,
+}
+
+impl<T> RcuCell<T> {
+	/// Create a new [`RcuCell`] with an initial value of `data`.
+	pub fn new(data: T) -> Self {
+		Self { current: RefCell::new(Rc::new(data)) }
+	}
+
+	/// Get the value inside the [`RcuCell`].
+	pub fn get(&self) -> CellGuard<T> {
+		CellGuard(Rc::clone(&self.current.borrow()))
+	}
+
+	/// Update the value inside the [`RcuCell`].
+	///
+	/// The old value is dropped once the last guard to it is dropped.
+	pub fn update(&self, new: T) {
+		drop(self.replace(new));
+	}
+
+	/// Update the value inside the [`RcuCell`] and return a guard to the
+	/// old one.
+	pub fn replace(&self, new: T) -> CellGuard<T> {
+		// The old value is returned instead of dropped here, so its `Drop`
+		// can safely access the cell again.
+		CellGuard(self.current.replace(Rc::new(new)))
+	}
+
+	/// Take the value out of the [`RcuCell`].
+	///
+	/// # Errors
+	///
+	/// Returns `Err(self)` if the current value is still referenced by a
+	/// guard.
+	pub fn try_into_inner(self) -> Result<T, Self> {
+		Rc::try_unwrap(self.current.into_inner())
+			.map_err(|x| Self { current: RefCell::new(x) })
+	}
+}
+
+impl<T: Default> Default for RcuCell<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T> From<T> for RcuCell<T> {
+	fn from(data: T) -> Self {
+		Self::new(data)
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&*self.get(), f)
+	}
+}
+
+/// The guard returned by [`RcuCell`].
+///
+/// See: [`RcuCell::get`].
+pub struct CellGuard<T>(Rc<T>);
+
+impl<T> Clone for CellGuard<T> {
+	fn clone(&self) -> Self {
+		Self(Rc::clone(&self.0))
+	}
+}
+
+impl<T> Deref for CellGuard<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for CellGuard<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: fmt::Display> fmt::Display for CellGuard<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&**self, f)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cell() {
+		let cell = RcuCell::new(1);
+
+		let old = cell.get();
+		cell.update(2);
+
+		assert_eq!(*old, 1);
+		assert_eq!(*cell.get(), 2);
+
+		let current = cell.get();
+		let cell = cell.try_into_inner().unwrap_err();
+		drop((old, current));
+		assert_eq!(cell.try_into_inner().ok(), Some(2));
+	}
+}
@@ -0,0 +1,278 @@
+use core::{cell::UnsafeCell, fmt, hint, mem::MaybeUninit, ptr};
+
+use portable_atomic::{fence, AtomicU8, AtomicUsize, Ordering};
+
+/// A type whose values are nothing but initialized bytes.
+///
+/// [`CopyRcu`] copies values in and out with atomic loads and stores of
+/// whole words, so it can only hold types for which that is sound.
+///
+/// # Safety
+///
+/// The type must not have padding bytes, and must not contain pointers or
+/// references. Those would lose their provenance when copied as integers.
+pub unsafe trait Plain: Copy {}
+
+macro_rules! impl_plain {
+	($($t:ty),*) => {
+		$(unsafe impl Plain for $t {})*
+	};
+}
+
+impl_plain!(
+	(),
+	bool,
+	char,
+	u8,
+	u16,
+	u32,
+	u64,
+	u128,
+	usize,
+	i8,
+	i16,
+	i32,
+	i64,
+	i128,
+	isize,
+	f32,
+	f64
+);
+
+unsafe impl<T: Plain, const N: usize> Plain for [T; N] {}
+
+/// A value aligned to a word, so it can be copied one word at a time.
+#[repr(C)]
+struct Words<T> {
+	_align: [usize; 0],
+	value: T,
+}
+
+impl<T> Words<T> {
+	/// The number of whole words in `T`.
+	const WORDS: usize = size_of::<T>() / size_of::<usize>();
+	/// The number of bytes in `T` after the whole words.
+	const BYTES: usize = size_of::<T>() % size_of::<usize>();
+}
+
+/// A lightweight alternative to [`Rcu`] for small [`Plain`] values.
+///
+/// The value is stored inline next to a sequence number instead of behind
+/// a pointer, so neither reads nor updates allocate or count references.
+/// Readers copy the value out and retry if an update was in progress
+/// while they did. Updates never wait for readers, only for each other.
+///
+/// Reads copy the whole value, possibly more than once, so this is only
+/// worth it for values that are cheap to copy.
+///
+/// [`Rcu`]: crate::Rcu
+pub struct CopyRcu<T: Plain> {
+	/// Odd while an update is in progress.
+	seq: AtomicUsize,
+	/// Only ever accessed with atomic loads and stores of the same size,
+	/// unless there is a mutable borrow.
+	data: UnsafeCell<Words<T>>,
+}
+
+impl<T: Plain> CopyRcu<T> {
+	/// Create a new [`CopyRcu`] with an initial value of `data`.
+	pub const fn new(data: T) -> Self {
+		Self {
+			seq: AtomicUsize::new(0),
+			data: UnsafeCell::new(Words { _align: [], value: data }),
+		}
+	}
+
+	/// Get a copy of the value.
+	pub fn get_copy(&self) -> T {
+		loop {
+			let before = self.seq.load(Ordering::Acquire);
+			if before & 1 != 0 {
+				hint::spin_loop();
+				continue;
+			}
+
+			// This might race with an update and read a torn value. It is
+			// only returned if the sequence shows that it did not.
+			let data = self.load();
+
+			// Keep the loads of `data` from moving after the check.
+			fence(Ordering::Acquire);
+			if self.seq.load(Ordering::Relaxed) == before {
+				return unsafe { data.assume_init().value };
+			}
+		}
+	}
+
+	/// Update the value to `data`.
+	pub fn update(&self, data: T) {
+		let mut seq = self.seq.load(Ordering::Relaxed);
+		loop {
+			if seq & 1 != 0 {
+				hint::spin_loop();
+				seq = self.seq.load(Ordering::Relaxed);
+				continue;
+			}
+
+			let r = self.seq.compare_exchange_weak(
+				seq,
+				seq.wrapping_add(1),
+				Ordering::Acquire,
+				Ordering::Relaxed,
+			);
+
+			match r {
+				Ok(_) => break,
+				Err(x) => seq = x,
+			}
+		}
+
+		// Keep the stores to `data` from moving before the sequence is odd.
+		fence(Ordering::Release);
+		self.store(&Words { _align: [], value: data });
+
+		self.seq.store(seq.wrapping_add(2), Ordering::Release);
+	}
+
+	/// Copy the value out with relaxed atomic loads.
+	///
+	/// `T` has no padding, so every byte of it is initialized.
+	fn load(&self) -> MaybeUninit<Words<T>> {
+		let mut out = MaybeUninit::<Words<T>>::uninit();
+
+		let src = self.data.get().cast::<usize>();
+		let dst = out.as_mut_ptr().cast::<usize>();
+		for i in 0..Words::<T>::WORDS {
+			unsafe {
+				let x = AtomicUsize::from_ptr(src.add(i));
+				dst.add(i).write(x.load(Ordering::Relaxed));
+			}
+		}
+
+		let src = unsafe { src.add(Words::<T>::WORDS).cast::<u8>() };
+		let dst = unsafe { dst.add(Words::<T>::WORDS).cast::<u8>() };
+		for i in 0..Words::<T>::BYTES {
+			unsafe {
+				let x = AtomicU8::from_ptr(src.add(i));
+				dst.add(i).write(x.load(Ordering::Relaxed));
+			}
+		}
+
+		out
+	}
+
+	/// Copy `data` in with relaxed atomic stores.
+	fn store(&self, data: &Words<T>) {
+		let src = ptr::from_ref(data).cast::<usize>();
+		let dst = self.data.get().cast::<usize>();
+		for i in 0..Words::<T>::WORDS {
+			unsafe {
+				let x = AtomicUsize::from_ptr(dst.add(i));
+				x.store(src.add(i).read(), Ordering::Relaxed);
+			}
+		}
+
+		let src = unsafe { src.add(Words::<T>::WORDS).cast::<u8>() };
+		let dst = unsafe { dst.add(Words::<T>::WORDS).cast::<u8>() };
+		for i in 0..Words::<T>::BYTES {
+			unsafe {
+				let x = AtomicU8::from_ptr(dst.add(i));
+				x.store(src.add(i).read(), Ordering::Relaxed);
+			}
+		}
+	}
+
+	/// Get a mutable reference to the value.
+	///
+	/// This is safe because the mutable borrow guarantees that no one else
+	/// is accessing the value.
+	pub const fn get_mut(&mut self) -> &mut T {
+		&mut self.data.get_mut().value
+	}
+
+	/// Take the value out of the [`CopyRcu`].
+	pub const fn into_inner(self) -> T {
+		self.data.into_inner().value
+	}
+}
+
+impl<T: Plain + Default> Default for CopyRcu<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T: Plain> From<T> for CopyRcu<T> {
+	fn from(data: T) -> Self {
+		Self::new(data)
+	}
+}
+
+impl<T: Plain + fmt::Debug> fmt::Debug for CopyRcu<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("CopyRcu").field(&self.get_copy()).finish()
+	}
+}
+
+// Values are copied between threads, never shared.
+unsafe impl<T: Plain + Send> Sync for CopyRcu<T> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::thread::scope;
+
+	use crate::testing::stress;
+
+	#[test]
+	fn test_copy_rcu() {
+		let rcu = CopyRcu::new([1, 2]);
+		assert_eq!(rcu.get_copy(), [1, 2]);
+
+		rcu.update([3, 4]);
+		assert_eq!(rcu.get_copy(), [3, 4]);
+		assert_eq!(rcu.into_inner(), [3, 4]);
+	}
+
+	#[test]
+	fn test_copy_rcu_bytes() {
+		// Not a whole number of words.
+		let rcu = CopyRcu::new([1u8; 11]);
+		rcu.update([2; 11]);
+		assert_eq!(rcu.get_copy(), [2; 11]);
+
+		let rcu = CopyRcu::new(true);
+		rcu.update(false);
+		assert!(!rcu.get_copy());
+	}
+
+	#[test]
+	fn test_copy_rcu_torn() {
+		const UPDATES: usize = if cfg!(miri) {
+			100
+		} else {
+			100_000
+		};
+
+		let rcu = CopyRcu::new([0; 8]);
+
+		// A second writer, so updates also race with each other.
+		scope(|scope| {
+			scope.spawn(|| {
+				for i in 1..=UPDATES {
+					rcu.update([i; 8]);
+				}
+			});
+
+			stress(
+				&rcu,
+				UPDATES,
+				|i| [i; 8],
+				|v| {
+					assert!(v.iter().all(|x| *x == v[0]));
+				},
+			);
+		});
+	}
+}
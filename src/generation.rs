@@ -0,0 +1,130 @@
+use core::{fmt, ops::Deref};
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::{Inner, Reclaim, RefCounting};
+
+/// A guard that can tell whether the [`Rcu`] has been updated since it
+/// was created.
+///
+/// This is meant for read-validate-retry loops: read through the guard,
+/// then [`validate`] that the value is still current before acting on
+/// what was read, and start over otherwise.
+///
+/// See: [`Rcu::generation_guard`].
+///
+/// [`validate`]: Self::validate
+pub struct GenerationGuard<'a, T, R: Reclaim = RefCounting> {
+	rcu: &'a Rcu<T, R>,
+	/// The node of `guard`, which it keeps from being reused.
+	node: *mut Inner<T>,
+	guard: Guard<'a, T>,
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Get the value inside the [`Rcu`] along with a way to check later
+	/// whether it has been updated in the meantime.
+	///
+	/// See: [`GenerationGuard`].
+	pub fn generation_guard(&self) -> GenerationGuard<'_, T, R> {
+		let (node, guard) = self.load_node();
+		GenerationGuard { rcu: self, node, guard }
+	}
+}
+
+impl<'a, T, R: Reclaim> GenerationGuard<'a, T, R> {
+	/// Check whether the value of the guard is still the current value
+	/// of the [`Rcu`].
+	///
+	/// Returns `false` if the [`Rcu`] has been updated since the guard
+	/// was created, even if the new value is equal to the old one.
+	#[must_use]
+	pub fn validate(&self) -> bool {
+		self.rcu.is_current(self.node)
+	}
+
+	/// Panic if the [`Rcu`] has been updated since the guard was created.
+	///
+	/// See: [`validate`].
+	///
+	/// [`validate`]: Self::validate
+	#[track_caller]
+	pub fn assert_valid(&self) {
+		assert!(
+			self.validate(),
+			"the Rcu was updated since the guard was created"
+		);
+	}
+
+	/// Turn this into a plain [`Guard`].
+	#[must_use]
+	pub fn into_guard(self) -> Guard<'a, T> {
+		self.guard
+	}
+}
+
+impl<T, R: Reclaim> Deref for GenerationGuard<'_, T, R> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.guard
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug
+	for GenerationGuard<'_, T, R>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: fmt::Display, R: Reclaim> fmt::Display
+	for GenerationGuard<'_, T, R>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&**self, f)
+	}
+}
+
+// `node` is only compared, never dereferenced. Otherwise these are the
+// bounds of `Guard` and of `&Rcu`.
+unsafe impl<T, R> Sync for GenerationGuard<'_, T, R>
+where
+	T: Send + Sync,
+	R: Reclaim + Sync,
+{
+}
+unsafe impl<T, R> Send for GenerationGuard<'_, T, R>
+where
+	T: Send + Sync,
+	R: Reclaim + Sync,
+{
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_generation_guard() {
+		let rcu = Rcu::new(1);
+
+		let guard = rcu.generation_guard();
+		assert!(guard.validate());
+		guard.assert_valid();
+
+		rcu.update(1);
+		assert_eq!(*guard, 1);
+		assert!(!guard.validate());
+	}
+
+	#[test]
+	#[should_panic = "the Rcu was updated since the guard was created"]
+	fn test_generation_guard_assert() {
+		let rcu = Rcu::new(1);
+
+		let guard = rcu.generation_guard();
+		rcu.update(2);
+		guard.assert_valid();
+	}
+}
@@ -0,0 +1,75 @@
+use core::{fmt, ops::Deref};
+
+use std::sync::Arc;
+
+use crate::rcu::Rcu;
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// A shared handle to an [`Rcu`].
+///
+/// This is an `Arc<Rcu<T, R>>` that can be created from an [`Rcu`] and
+/// dereferences to it. Clone it to share the [`Rcu`] with threads that
+/// are not bound to a specific scope.
+pub struct RcuHandle<T, R: Reclaim = RefCounting> {
+	rcu: Arc<Rcu<T, R>>,
+}
+
+impl<T> RcuHandle<T> {
+	/// Create a new [`RcuHandle`] to a new [`Rcu`] with an initial value of
+	/// `data`.
+	pub fn new(data: T) -> Self {
+		Self::from(Rcu::new(data))
+	}
+}
+
+impl<T, R: Reclaim> Clone for RcuHandle<T, R> {
+	fn clone(&self) -> Self {
+		Self { rcu: Arc::clone(&self.rcu) }
+	}
+}
+
+impl<T, R: Reclaim> From<Rcu<T, R>> for RcuHandle<T, R> {
+	fn from(rcu: Rcu<T, R>) -> Self {
+		Self { rcu: Arc::new(rcu) }
+	}
+}
+
+impl<T, R: Reclaim> Deref for RcuHandle<T, R> {
+	type Target = Rcu<T, R>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.rcu
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug for RcuHandle<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&*self.rcu, f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::thread;
+
+	#[test]
+	fn test_handle() {
+		let handle = RcuHandle::new(0);
+
+		let threads: Vec<_> = (1..=4)
+			.map(|i| {
+				let handle = handle.clone();
+				thread::spawn(move || handle.update(i))
+			})
+			.collect();
+
+		for t in threads {
+			t.join().unwrap();
+		}
+
+		assert!((1..=4).contains(&*handle.get()));
+		assert_eq!(handle.generation(), 4);
+	}
+}
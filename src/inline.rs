@@ -0,0 +1,257 @@
+use core::{cell::UnsafeCell, fmt, hint, ops::Deref};
+
+use portable_atomic::{fence, AtomicUsize, Ordering};
+
+/// A slot for one value of an [`InlineRcu`].
+struct Slot<T> {
+	/// The number of guards to the value, plus one if it is the current
+	/// value. The slot is free once this drops to zero.
+	refs: AtomicUsize,
+	/// Only written by the [`update`] that took the slot while it was
+	/// free. A value stays here until the slot is reused.
+	///
+	/// [`update`]: InlineRcu::update
+	data: UnsafeCell<Option<T>>,
+}
+
+impl<T> Slot<T> {
+	const fn new() -> Self {
+		Self {
+			refs: AtomicUsize::new(0),
+			data: UnsafeCell::new(None),
+		}
+	}
+
+	/// Take the slot if it is free.
+	fn claim(&self) -> bool {
+		// Synchronize with the release of the last guard, so all accesses
+		// through it happen before we overwrite the value.
+		self.refs
+			.compare_exchange(
+				0,
+				1,
+				Ordering::Acquire,
+				Ordering::Relaxed,
+			)
+			.is_ok()
+	}
+
+	fn release(&self) {
+		self.refs.fetch_sub(1, Ordering::Release);
+	}
+}
+
+/// An [`Rcu`] that stores its values inline instead of on the heap.
+///
+/// The values live in `SLOTS` slots inside the [`InlineRcu`] itself, so
+/// nothing is ever allocated. An [`update`] writes the new value to a free
+/// slot and makes it current. The old slot becomes free again once its
+/// last guard is dropped. Old values are only dropped when their slot is
+/// reused, or when the [`InlineRcu`] is dropped.
+///
+/// One slot always holds the current value, so `SLOTS` must be large
+/// enough to also hold every old value that might still be in use by a
+/// guard, plus the values of concurrent updates. If all slots are in use,
+/// [`update`] panics in debug builds and waits for a slot to become free
+/// otherwise. `SLOTS` must be at least 2.
+///
+/// [`Rcu`]: crate::Rcu
+/// [`update`]: Self::update
+pub struct InlineRcu<T, const SLOTS: usize> {
+	slots: [Slot<T>; SLOTS],
+	/// The index of the slot of the current value.
+	current: AtomicUsize,
+	/// The number of [`get`] calls that have loaded `current` but not yet
+	/// taken a ref. Same as for [`RefCounting`].
+	///
+	/// [`get`]: Self::get
+	/// [`RefCounting`]: crate::reclaim::RefCounting
+	loading: AtomicUsize,
+}
+
+impl<T, const SLOTS: usize> InlineRcu<T, SLOTS> {
+	/// Create a new [`InlineRcu`] with an initial value of `data`.
+	pub fn new(data: T) -> Self {
+		const {
+			assert!(
+				SLOTS >= 2,
+				"an InlineRcu needs at least 2 slots"
+			);
+		}
+
+		let mut slots = [const { Slot::new() }; SLOTS];
+		*slots[0].refs.get_mut() = 1;
+		*slots[0].data.get_mut() = Some(data);
+
+		Self {
+			slots,
+			current: AtomicUsize::new(0),
+			loading: AtomicUsize::new(0),
+		}
+	}
+
+	/// Get the value inside the [`InlineRcu`].
+	///
+	/// This function does _not_ block execution.
+	pub fn get(&self) -> InlineGuard<'_, T> {
+		// See `RefCounting::protect`.
+		self.loading.fetch_add(1, Ordering::SeqCst);
+		fence(Ordering::SeqCst);
+		let slot = &self.slots[self.current.load(Ordering::SeqCst)];
+		slot.refs.fetch_add(1, Ordering::Relaxed);
+		self.loading.fetch_sub(1, Ordering::Release);
+
+		InlineGuard { slot }
+	}
+
+	/// Update the value inside the [`InlineRcu`].
+	///
+	/// This function does _not_ allocate.
+	///
+	/// # Panics
+	///
+	/// In debug builds, if all slots are in use.
+	pub fn update(&self, new: T) {
+		let slot = self.claim();
+
+		// We own the slot, nobody else can access its value.
+		unsafe { *self.slots[slot].data.get() = Some(new) };
+
+		// Release publishes the value to readers that load `slot`.
+		let old = self.current.swap(slot, Ordering::SeqCst);
+
+		// See `RefCounting::retire`.
+		fence(Ordering::SeqCst);
+		while self.loading.load(Ordering::SeqCst) != 0 {
+			hint::spin_loop();
+		}
+
+		self.slots[old].release();
+	}
+
+	/// Take a free slot.
+	fn claim(&self) -> usize {
+		loop {
+			if let Some(i) = self.slots.iter().position(Slot::claim) {
+				return i;
+			}
+
+			debug_assert!(
+				false,
+				"all slots of the InlineRcu are in use"
+			);
+			hint::spin_loop();
+		}
+	}
+}
+
+impl<T: Default, const SLOTS: usize> Default for InlineRcu<T, SLOTS> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T: fmt::Debug, const SLOTS: usize> fmt::Debug
+	for InlineRcu<T, SLOTS>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("InlineRcu").field(&*self.get()).finish()
+	}
+}
+
+// Same as for `Rcu`.
+unsafe impl<T: Send + Sync, const SLOTS: usize> Sync
+	for InlineRcu<T, SLOTS>
+{
+}
+unsafe impl<T: Send, const SLOTS: usize> Send
+	for InlineRcu<T, SLOTS>
+{
+}
+
+/// The RAII guard returned by [`InlineRcu`].
+///
+/// See: [`InlineRcu::get`].
+pub struct InlineGuard<'a, T> {
+	slot: &'a Slot<T>,
+}
+
+impl<T> Deref for InlineGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		// The slot cannot be reused while we hold a ref to it, and it
+		// always holds a value once it can be loaded by `get`.
+		unsafe { (*self.slot.data.get()).as_ref().unwrap_unchecked() }
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for InlineGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: fmt::Display> fmt::Display for InlineGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&**self, f)
+	}
+}
+
+impl<T> Drop for InlineGuard<'_, T> {
+	fn drop(&mut self) {
+		self.slot.release();
+	}
+}
+
+unsafe impl<T: Sync> Sync for InlineGuard<'_, T> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use crate::testing::{stress, READERS};
+
+	#[test]
+	fn test_inline() {
+		let rcu = InlineRcu::<_, 2>::new(1);
+
+		let old = rcu.get();
+		rcu.update(2);
+		assert_eq!(*old, 1);
+		assert_eq!(*rcu.get(), 2);
+
+		drop(old);
+		for i in 3..10 {
+			rcu.update(i);
+		}
+		assert_eq!(*rcu.get(), 9);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic = "all slots of the InlineRcu are in use"]
+	fn test_inline_full() {
+		let rcu = InlineRcu::<_, 2>::new(1);
+
+		let _a = rcu.get();
+		rcu.update(2);
+		let _b = rcu.get();
+		rcu.update(3);
+	}
+
+	#[test]
+	fn test_inline_concurrent() {
+		// One slot for the current value, one for every reader and one for
+		// the update.
+		let rcu = InlineRcu::<_, { READERS + 2 }>::new([0; 16]);
+		stress(
+			&rcu,
+			10_000,
+			|i| [i; 16],
+			|v| {
+				assert!(v.iter().all(|x| *x == v[0]));
+			},
+		);
+	}
+}
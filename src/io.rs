@@ -0,0 +1,120 @@
+use std::io;
+
+use crate::rcu::Rcu;
+use crate::reclaim::Reclaim;
+
+/// Write to the current value.
+///
+/// [`Write`] takes `&mut self`, but a guard only gives out `&T`. So this
+/// is only implemented if `&T` implements [`Write`] itself, like
+/// [`File`] and [`Stdout`] do. Each call writes to the value that is
+/// current at the time it is made, so a single [`write_all`] might end up
+/// split across values if the [`Rcu`] is updated in the middle of it.
+///
+/// [`Write`]: io::Write
+/// [`File`]: std::fs::File
+/// [`Stdout`]: std::io::Stdout
+/// [`write_all`]: io::Write::write_all
+impl<T, R: Reclaim> io::Write for &Rcu<T, R>
+where
+	for<'a> &'a T: io::Write,
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		(&*self.get()).write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		(&*self.get()).flush()
+	}
+}
+
+impl<T, R: Reclaim> io::Write for Rcu<T, R>
+where
+	for<'a> &'a T: io::Write,
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		(&*self).write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		(&*self).flush()
+	}
+}
+
+/// Read from the current value.
+///
+/// Like the [`Write`] implementation, this needs `&T` to implement
+/// [`Read`], like [`File`] does.
+///
+/// [`Write`]: io::Write
+/// [`Read`]: io::Read
+/// [`File`]: std::fs::File
+impl<T, R: Reclaim> io::Read for &Rcu<T, R>
+where
+	for<'a> &'a T: io::Read,
+{
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		(&*self.get()).read(buf)
+	}
+}
+
+impl<T, R: Reclaim> io::Read for Rcu<T, R>
+where
+	for<'a> &'a T: io::Read,
+{
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		(&*self).read(buf)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::io::{Read, Write};
+	use std::sync::Mutex;
+
+	#[derive(Default)]
+	struct Sink(Mutex<Vec<u8>>);
+
+	impl Write for &Sink {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	impl Read for &Sink {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let mut data = self.0.lock().unwrap();
+			let n = (&data[..]).read(buf)?;
+			data.drain(..n);
+			Ok(n)
+		}
+	}
+
+	#[test]
+	fn test_write() {
+		let rcu = Rcu::new(Sink::default());
+
+		(&rcu).write_all(b"hello").unwrap();
+		let old = rcu.replace(Sink::default());
+		(&rcu).write_all(b"world").unwrap();
+
+		assert_eq!(*old.0.lock().unwrap(), b"hello");
+		assert_eq!(*rcu.get().0.lock().unwrap(), b"world");
+	}
+
+	#[test]
+	fn test_read() {
+		let mut rcu = Rcu::new(Sink::default());
+		rcu.write_all(b"hello").unwrap();
+
+		let mut buf = String::new();
+		rcu.read_to_string(&mut buf).unwrap();
+		assert_eq!(buf, "hello");
+	}
+}
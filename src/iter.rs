@@ -0,0 +1,228 @@
+use core::{fmt, iter::FusedIterator};
+
+use alloc::vec::Vec;
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::Reclaim;
+
+/// An iterator over the elements of the value of an `Rcu<Vec<T>>`.
+///
+/// The iterator walks the vector that was current when it was created. It
+/// never sees later updates. Each element is yielded as a guard of its
+/// own, so elements can outlive the iterator.
+///
+/// See: [`Rcu`].
+#[derive(Debug)]
+pub struct RcuVecIter<'a, T> {
+	guard: Guard<'a, Vec<T>>,
+	pos: usize,
+}
+
+impl<'a, T> Iterator for RcuVecIter<'a, T> {
+	type Item = Guard<'a, T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.pos;
+		if i >= self.guard.len() {
+			return None;
+		}
+
+		self.pos += 1;
+
+		// `T` is `Send` and `Sync` exactly when `Vec<T>` is.
+		let guard = Guard::duplicate(&self.guard);
+		Some(unsafe { Guard::map_unchecked(guard, move |x| &x[i]) })
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = self.guard.len() - self.pos;
+		(n, Some(n))
+	}
+}
+
+impl<T> ExactSizeIterator for RcuVecIter<'_, T> {}
+
+impl<T> FusedIterator for RcuVecIter<'_, T> {}
+
+impl<T, R: Reclaim> Rcu<Vec<T>, R> {
+	/// Iterate over the elements of the current value.
+	///
+	/// See: [`RcuVecIter`].
+	pub fn iter(&self) -> RcuVecIter<'_, T> {
+		RcuVecIter { guard: self.get(), pos: 0 }
+	}
+}
+
+/// Iterate over the elements of the current value.
+///
+/// See: [`RcuVecIter`].
+impl<'a, T, R: Reclaim> IntoIterator for &'a Rcu<Vec<T>, R> {
+	type Item = Guard<'a, T>;
+	type IntoIter = RcuVecIter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// A function that returns an iterator over a value it borrows.
+///
+/// This is implemented for every function that takes `&T` and returns an
+/// iterator. It only exists so that [`Rcu::map_iter`] can name the type
+/// of that iterator for every lifetime of the borrow.
+pub trait MapIterFn<'g, T: 'g>: FnOnce(&'g T) -> Self::Iter {
+	/// The iterator returned by the function.
+	type Iter: Iterator;
+}
+
+impl<'g, T: 'g, F, I> MapIterFn<'g, T> for F
+where
+	F: FnOnce(&'g T) -> I,
+	I: Iterator,
+{
+	type Iter = I;
+}
+
+/// An iterator over a snapshot of the value of an [`Rcu`].
+///
+/// This keeps the value that was current when it was created alive, so
+/// the iterator that borrows from it stays valid even if the [`Rcu`] is
+/// updated while iterating.
+///
+/// See: [`Rcu::map_iter`].
+pub struct RcuIter<'a, T, I> {
+	/// Borrows from the value kept alive by `_guard`, so it is dropped
+	/// first.
+	iter: I,
+	_guard: Guard<'a, T>,
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Iterate over the items of the iterator that `f` returns for the
+	/// current value.
+	///
+	/// The iterator may borrow from the value, but its items may not,
+	/// because they can outlive the [`RcuIter`]. Closures cannot return
+	/// an iterator that borrows from their argument, so `f` is usually a
+	/// function:
+	///
+	/// ```
+	/// use std::collections::BTreeMap;
+	///
+	/// use rcurs::Rcu;
+	///
+	/// fn sums(x: &BTreeMap<i32, i32>) -> impl Iterator<Item = i32> + '_ {
+	///     x.iter().map(|(k, v)| k + v)
+	/// }
+	///
+	/// let rcu = Rcu::new(BTreeMap::from([(1, 2), (3, 4)]));
+	///
+	/// let iter = rcu.map_iter(sums);
+	/// rcu.update(BTreeMap::new());
+	/// assert_eq!(iter.collect::<Vec<_>>(), [3, 7]);
+	/// ```
+	///
+	/// Items that borrow from the value are rejected:
+	///
+	/// ```compile_fail
+	/// use std::collections::BTreeMap;
+	///
+	/// use rcurs::Rcu;
+	///
+	/// let rcu = Rcu::new(BTreeMap::from([(1, 2)]));
+	/// let _ = rcu.map_iter(BTreeMap::values);
+	/// ```
+	///
+	/// See: [`RcuIter`].
+	pub fn map_iter<U, F>(
+		&self,
+		f: F,
+	) -> RcuIter<'_, T, <F as MapIterFn<'_, T>>::Iter>
+	where
+		F: for<'g> MapIterFn<'g, T, Iter: Iterator<Item = U>>,
+	{
+		let guard = self.get();
+
+		// The value stays alive for as long as `guard`, which outlives
+		// `iter`. Because `f` works for any lifetime, neither it nor the
+		// items of `iter` can keep the reference past `iter`.
+		let iter = f(unsafe { &*Guard::as_ptr(&guard) });
+
+		RcuIter { iter, _guard: guard }
+	}
+}
+
+impl<T, I: Iterator> Iterator for RcuIter<'_, T, I> {
+	type Item = I::Item;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.iter.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.iter.size_hint()
+	}
+}
+
+impl<T, I: ExactSizeIterator> ExactSizeIterator
+	for RcuIter<'_, T, I>
+{
+}
+
+impl<T, I: FusedIterator> FusedIterator for RcuIter<'_, T, I> {}
+
+impl<T, I> fmt::Debug for RcuIter<'_, T, I> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("RcuIter").finish_non_exhaustive()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::collections::BTreeMap;
+
+	#[test]
+	fn test_iter() {
+		let rcu = Rcu::new(vec![1, 2, 3]);
+
+		let mut iter = rcu.iter();
+		assert_eq!(*iter.next().unwrap(), 1);
+
+		rcu.update(vec![4]);
+		let rest: Vec<_> = iter.map(|x| *x).collect();
+		assert_eq!(rest, [2, 3]);
+
+		let mut sum = 0;
+		for x in &rcu {
+			sum += *x;
+		}
+		assert_eq!(sum, 4);
+	}
+
+	#[test]
+	fn test_map_iter() {
+		fn names(
+			x: &BTreeMap<i32, String>,
+		) -> impl ExactSizeIterator<Item = String> + '_ {
+			x.values().cloned()
+		}
+
+		let rcu = Rcu::new(BTreeMap::from([
+			(1, "a".to_owned()),
+			(2, "b".to_owned()),
+		]));
+
+		let mut iter = rcu.map_iter(names);
+		assert_eq!(iter.len(), 2);
+		assert_eq!(iter.next().unwrap(), "a");
+
+		rcu.update(BTreeMap::new());
+		assert_eq!(iter.collect::<Vec<_>>(), ["b"]);
+
+		let keys =
+			rcu.map_iter(|x: &BTreeMap<_, _>| x.clone().into_keys());
+		assert_eq!(keys.count(), 0);
+	}
+}
@@ -0,0 +1,129 @@
+//! Layout introspection, for debugging alignment issues.
+//!
+//! The value of an [`Rcu`] lives in an [`Inner`] right after its ref
+//! count, which is aligned to a cache line of its own. Use these functions
+//! to check how that works out for a specific type.
+//!
+//! [`Rcu`]: crate::Rcu
+
+use core::alloc::Layout;
+use core::mem;
+
+use crate::rcu::Rcu;
+use crate::reclaim::{Inner, Reclaim};
+use crate::refs::Refs;
+
+/// The size of the cache line that the ref count is aligned to.
+pub const CACHE_LINE: usize = mem::align_of::<Refs>();
+
+/// Get the layout of the node that holds a value of type `T`.
+#[must_use]
+pub const fn rcu_inner_layout<T>() -> Layout {
+	Layout::new::<Inner<T>>()
+}
+
+/// Get the layout of an [`Rcu`] itself, not including its value.
+#[must_use]
+pub const fn rcu_layout<T, R: Reclaim>() -> Layout {
+	Layout::new::<Rcu<T, R>>()
+}
+
+/// Get the offset of the ref count in the node that holds a value of type
+/// `T`.
+#[must_use]
+pub const fn refs_offset<T>() -> usize {
+	mem::offset_of!(Inner<T>, refs)
+}
+
+/// Get the offset of the value in the node that holds a value of type
+/// `T`.
+#[must_use]
+pub const fn data_offset<T>() -> usize {
+	mem::offset_of!(Inner<T>, data)
+}
+
+/// Check whether the value of type `T` starts on a different cache line
+/// than the ref count.
+///
+/// The layout of [`Inner`] guarantees this for every `T`, so this never
+/// returns `false` with this version of the crate. It is meant as a
+/// regression guard, for code that relies on the separation and wants to
+/// notice if a future version changes the layout.
+#[must_use]
+pub const fn is_cache_line_separated<T>() -> bool {
+	data_offset::<T>() >= refs_offset::<T>() + CACHE_LINE
+}
+
+/// Print the layout of the node that holds a value of type `T`, and that
+/// of an [`Rcu`] using `R`.
+#[cfg(feature = "std")]
+pub fn print_layout<T, R: Reclaim>() {
+	let inner = rcu_inner_layout::<T>();
+	let rcu = rcu_layout::<T, R>();
+
+	std::println!("Inner<{}>:", core::any::type_name::<T>());
+	std::println!("  size:  {}", inner.size());
+	std::println!("  align: {}", inner.align());
+	std::println!("  refs:  offset {}", refs_offset::<T>());
+	std::println!("  data:  offset {}", data_offset::<T>());
+	std::println!("Rcu<_, {}>:", core::any::type_name::<R>());
+	std::println!("  size:  {}", rcu.size());
+	std::println!("  align: {}", rcu.align());
+}
+
+/// Fail compilation unless the values of the given types start on a
+/// different cache line than their ref counts.
+///
+/// Like [`is_cache_line_separated`], which it is built on, this cannot
+/// fail with this version of the crate. It guards against future layout
+/// changes.
+///
+/// [`is_cache_line_separated`]: crate::layout::is_cache_line_separated
+///
+/// ```
+/// rcurs::static_assert_layout!(u8, [u64; 32], String);
+/// ```
+#[macro_export]
+macro_rules! static_assert_layout {
+	($($ty:ty),+ $(,)?) => {
+		$(
+			const _: () = ::core::assert!(
+				$crate::layout::is_cache_line_separated::<$ty>(),
+				::core::concat!(
+					"the value of `Inner<",
+					::core::stringify!($ty),
+					">` shares a cache line with its ref count",
+				),
+			);
+		)+
+	};
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use crate::reclaim::RefCounting;
+
+	#[test]
+	fn test_layout() {
+		#[repr(align(256))]
+		struct Aligned;
+
+		assert_eq!(refs_offset::<u8>(), 0);
+		assert_eq!(data_offset::<u8>(), CACHE_LINE);
+		assert_eq!(rcu_inner_layout::<u8>().size(), 2 * CACHE_LINE);
+		assert_eq!(rcu_inner_layout::<u8>().align(), CACHE_LINE);
+		assert!(rcu_layout::<u8, RefCounting>().size() > 0);
+
+		// A value aligned to more than a cache line pushes itself further
+		// away from the ref count.
+		assert_eq!(refs_offset::<Aligned>(), 0);
+		assert_eq!(data_offset::<Aligned>(), 256);
+		assert_eq!(rcu_inner_layout::<Aligned>().align(), 256);
+
+		assert!(is_cache_line_separated::<u8>());
+		assert!(is_cache_line_separated::<[u64; 32]>());
+		assert!(is_cache_line_separated::<Aligned>());
+	}
+}
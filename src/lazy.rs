@@ -0,0 +1,142 @@
+use core::{fmt, marker::PhantomData, ptr};
+
+use alloc::boxed::Box;
+
+use portable_atomic::{AtomicPtr, Ordering};
+
+use crate::rcu::{Guard, Rcu};
+
+/// An [`Rcu`] that starts out empty and is initialized later.
+///
+/// Until [`init`] is called, [`try_get`] returns [`None`] and [`get`]
+/// blocks. Afterwards, this is an [`Rcu`] that can be updated as usual
+/// through [`rcu`].
+///
+/// [`init`]: Self::init
+/// [`try_get`]: Self::try_get
+/// [`get`]: Self::get
+/// [`rcu`]: Self::rcu
+pub struct LazyRcu<T> {
+	/// Null until initialized. Never changes afterwards.
+	rcu: AtomicPtr<Rcu<T>>,
+	_marker: PhantomData<Box<Rcu<T>>>,
+}
+
+impl<T> LazyRcu<T> {
+	/// Create a new uninitialized [`LazyRcu`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			rcu: AtomicPtr::new(ptr::null_mut()),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Initialize the [`LazyRcu`] with a value of `data`.
+	///
+	/// # Errors
+	///
+	/// Returns `Err(data)` if the [`LazyRcu`] has already been
+	/// initialized.
+	pub fn init(&self, data: T) -> Result<(), T> {
+		let new = Box::into_raw(Box::new(Rcu::new(data)));
+
+		let r = self.rcu.compare_exchange(
+			ptr::null_mut(),
+			new,
+			Ordering::AcqRel,
+			Ordering::Relaxed,
+		);
+
+		r.map(|_| ())
+			.map_err(|_| Rcu::take(*unsafe { Box::from_raw(new) }))
+	}
+
+	/// Get the [`Rcu`] if the [`LazyRcu`] has been initialized.
+	pub fn rcu(&self) -> Option<&Rcu<T>> {
+		unsafe { self.rcu.load(Ordering::Acquire).as_ref() }
+	}
+
+	/// Get the value if the [`LazyRcu`] has been initialized.
+	///
+	/// This function does _not_ block execution.
+	pub fn try_get(&self) -> Option<Guard<'_, T>> {
+		self.rcu().map(Rcu::get)
+	}
+
+	/// Get the value, waiting for the [`LazyRcu`] to be initialized if
+	/// needed.
+	///
+	/// This function blocks execution until [`init`] is called.
+	///
+	/// [`init`]: Self::init
+	pub fn get(&self) -> Guard<'_, T> {
+		loop {
+			if let Some(x) = self.try_get() {
+				return x;
+			}
+
+			#[cfg(feature = "std")]
+			std::thread::yield_now();
+			#[cfg(not(feature = "std"))]
+			core::hint::spin_loop();
+		}
+	}
+}
+
+impl<T> Default for LazyRcu<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for LazyRcu<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut f = f.debug_tuple("LazyRcu");
+		match self.rcu() {
+			Some(x) => f.field(&*x.get()),
+			None => f.field(&format_args!("<uninit>")),
+		};
+		f.finish()
+	}
+}
+
+impl<T> Drop for LazyRcu<T> {
+	fn drop(&mut self) {
+		let rcu = *self.rcu.get_mut();
+		if !rcu.is_null() {
+			drop(unsafe { Box::from_raw(rcu) });
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::thread::scope;
+
+	#[test]
+	fn test_lazy() {
+		let lazy = LazyRcu::new();
+		assert!(lazy.try_get().is_none());
+
+		assert_eq!(lazy.init(1), Ok(()));
+		assert_eq!(lazy.init(2), Err(2));
+		assert_eq!(*lazy.get(), 1);
+
+		lazy.rcu().unwrap().update(3);
+		assert_eq!(*lazy.try_get().unwrap(), 3);
+	}
+
+	#[test]
+	fn test_lazy_get_blocks() {
+		let lazy = LazyRcu::new();
+
+		scope(|scope| {
+			let reader = scope.spawn(|| *lazy.get());
+			lazy.init(1).unwrap();
+			assert_eq!(reader.join().unwrap(), 1);
+		});
+	}
+}
@@ -84,6 +84,40 @@
 //!     });
 //! }
 //! ```
+//!
+//! # `no_std`
+//!
+//! Disabling the default `std` feature makes this crate `no_std`. It still
+//! needs `alloc`.
+//!
+//! All atomics come from [`portable-atomic`], so targets without native
+//! atomic compare-and-swap, like `thumbv6m-none-eabi`, are supported as
+//! well. On those, `portable-atomic` needs to be told how to make its
+//! operations atomic. Either enable its `critical-section` feature in your
+//! own `Cargo.toml` and provide a [`critical-section`] implementation, or,
+//! on single-core targets only, build with
+//! `--cfg portable_atomic_unsafe_assume_single_core`:
+//!
+//! ```toml
+//! [dependencies]
+//! rcurs = { version = "0.2", default-features = false }
+//! portable-atomic = { version = "1", features = ["critical-section"] }
+//! ```
+//!
+//! Some counters are 64-bit, so this crate enables the `fallback` feature
+//! of `portable-atomic`. It provides 64-bit atomics on targets that only
+//! have 32-bit ones, with global locks or, together with one of the
+//! options above, with critical sections.
+//!
+//! | Target | Example | What you need |
+//! |--------|---------|---------------|
+//! | 64-bit atomics | `x86_64`, `aarch64`, `riscv64gc-*` | Nothing |
+//! | 32-bit atomic CAS only | `thumbv7em-none-eabi`, `riscv32imac-*` | Nothing |
+//! | No atomic CAS, single-core | `thumbv6m-none-eabi`, `riscv32imc-*` | `portable_atomic_unsafe_assume_single_core` |
+//! | No atomic CAS, multi-core | | `critical-section` |
+//!
+//! [`portable-atomic`]: https://docs.rs/portable-atomic
+//! [`critical-section`]: https://docs.rs/critical-section
 #![deny(missing_docs)]
 #![warn(
 	clippy::all,
@@ -97,13 +131,103 @@
 #![allow(
 	clippy::missing_panics_doc,
 	clippy::significant_drop_tightening,
-	clippy::needless_lifetimes
+	clippy::needless_lifetimes,
+	clippy::elidable_lifetime_names
 )]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+mod arc;
+mod batch;
+mod cell;
+mod copy;
+mod generation;
+#[cfg(feature = "std")]
+mod handle;
+mod inline;
+#[cfg(feature = "std")]
+mod io;
+mod iter;
+pub mod layout;
+mod lazy;
+#[cfg(feature = "std")]
+mod macros;
+mod observer;
+mod parts;
 mod rcu;
+mod reader;
+pub mod reclaim;
 mod refs;
+mod single_writer;
+mod snapshot;
+#[cfg(feature = "std")]
+mod split;
+#[cfg(all(test, feature = "std"))]
+mod testing;
+mod view;
+#[cfg(feature = "std")]
+mod watch;
+#[cfg(feature = "std")]
+mod write_lock;
+
+pub use self::batch::BatchUpdate;
+pub use self::cell::{CellGuard, RcuCell};
+pub use self::copy::{CopyRcu, Plain};
+pub use self::generation::GenerationGuard;
+#[cfg(feature = "std")]
+pub use self::handle::RcuHandle;
+pub use self::inline::{InlineGuard, InlineRcu};
+pub use self::iter::{MapIterFn, RcuIter, RcuVecIter};
+pub use self::lazy::LazyRcu;
+pub use self::observer::Observer;
+pub use self::parts::RcuDropGuard;
+pub use self::rcu::{Guard, OldGuard, Rcu, TryUpdateError};
+pub use self::reader::ThreadReader;
+pub use self::refs::RefsOverflow;
+pub use self::single_writer::SingleWriterRcu;
+pub use self::snapshot::Snapshot;
+#[cfg(feature = "std")]
+pub use self::split::{split, RcuReader, RcuWriter};
+pub use self::view::RcuView;
+#[cfg(feature = "std")]
+pub use self::watch::Watch;
+#[cfg(feature = "std")]
+pub use self::write_lock::WriteLockRcu;
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod __private {
+	pub use std::sync::LazyLock;
+}
+
+/// An [`Rcu`] holding a string that can be appended to.
+///
+/// See: [`Rcu::push_str`].
+pub type RcuString = Rcu<alloc::string::String>;
+
+/// An [`Rcu`] holding a value that is already behind an `Arc`.
+///
+/// This also lets several [`Rcu`]s share one allocation of the same value:
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use rcurs::ArcRcu;
+///
+/// let schema = Arc::new(vec!["id", "name"]);
+/// let users = ArcRcu::new(Arc::clone(&schema));
+/// let groups = ArcRcu::new(Arc::new(Vec::new()));
+///
+/// groups.update_arc(Arc::clone(&schema));
+/// assert!(Arc::ptr_eq(&users.get_arc(), &groups.get_arc()));
+/// ```
+///
+/// See: [`Rcu::update_arc`].
+#[cfg(feature = "std")]
+pub type ArcRcu<T> = Rcu<std::sync::Arc<T>>;
 
-pub use self::rcu::{Guard, Rcu};
+/// An [`Rcu`] that uses [epoch-based reclamation](reclaim::Epoch).
+#[cfg(feature = "std")]
+pub type EpochRcu<T> = Rcu<T, reclaim::Epoch>;
@@ -0,0 +1,38 @@
+/// Declare `static` [`Rcu`]s.
+///
+/// [`Rcu::new`] is not `const`, so an [`Rcu`] cannot be the initializer
+/// of a `static`. This macro wraps each [`Rcu`] in a [`LazyLock`] instead,
+/// which creates it on first access. The initializer is the initial
+/// value of the [`Rcu`], converted with [`From`].
+///
+/// Any number of items, with attributes and visibility, can be declared
+/// in one invocation.
+///
+/// ```rust
+/// use rcurs::{static_rcu, Rcu};
+///
+/// static_rcu! {
+///     /// The current configuration.
+///     pub static CONFIG: Rcu<String> = String::from("default");
+///     static COUNTER: Rcu<u32> = 0;
+/// }
+///
+/// CONFIG.update(String::from("custom"));
+/// assert_eq!(*CONFIG.get(), "custom");
+/// assert_eq!(*COUNTER.get(), 0);
+/// ```
+///
+/// [`Rcu`]: crate::Rcu
+/// [`Rcu::new`]: crate::Rcu::new
+/// [`LazyLock`]: std::sync::LazyLock
+#[macro_export]
+macro_rules! static_rcu {
+	($(
+		$(#[$attr:meta])*
+		$vis:vis static $name:ident: $ty:ty = $init:expr;
+	)*) => {$(
+		$(#[$attr])*
+		$vis static $name: $crate::__private::LazyLock<$ty> =
+			$crate::__private::LazyLock::new(|| <$ty>::from($init));
+	)*};
+}
@@ -0,0 +1,26 @@
+/// A hook that is called every time the value of an [`Rcu`] is replaced.
+///
+/// Closures taking the old and the new value implement this trait.
+///
+/// See: [`Rcu::with_observer`].
+///
+/// [`Rcu`]: crate::Rcu
+/// [`Rcu::with_observer`]: crate::Rcu::with_observer
+pub trait Observer<T> {
+	/// Called after `new` has replaced `old`.
+	///
+	/// This runs on the thread that did the update, before the update
+	/// returns. Both values are kept alive for the duration of the call.
+	/// Readers might already see `new`, and further updates might already
+	/// have replaced it.
+	fn on_update(&self, old: &T, new: &T);
+}
+
+impl<T, F> Observer<T> for F
+where
+	F: Fn(&T, &T),
+{
+	fn on_update(&self, old: &T, new: &T) {
+		self(old, new);
+	}
+}
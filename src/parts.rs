@@ -0,0 +1,97 @@
+use core::{fmt, marker::PhantomData};
+
+use alloc::boxed::Box;
+
+use portable_atomic::AtomicPtr;
+
+use crate::rcu::Rcu;
+use crate::reclaim::Inner;
+use crate::refs::Refs;
+
+impl<T> Rcu<T> {
+	/// Split the [`Rcu`] into the raw pointer to its current value and a
+	/// guard that frees that value.
+	///
+	/// This is an escape hatch for embedding the pointer somewhere this
+	/// crate cannot manage it, like a C struct. Everything else the
+	/// [`Rcu`] owns is dropped. The value stays alive until the returned
+	/// [`RcuDropGuard`] is dropped.
+	///
+	/// # Safety
+	///
+	/// - The value must not be accessed through the pointer after the
+	///   [`RcuDropGuard`] is dropped.
+	/// - The value must not be freed in any other way. The guard frees the
+	///   node that was current when the [`Rcu`] was split, even if another
+	///   node is stored in the returned [`AtomicPtr`] later. Freeing any
+	///   such node is up to the caller.
+	/// - Refs taken on the node through the pointer must be released
+	///   before the guard is dropped.
+	pub unsafe fn into_parts(
+		this: Self,
+	) -> (AtomicPtr<Inner<T>>, RcuDropGuard<T>) {
+		let node = this.into_node();
+		let guard = RcuDropGuard { node, _marker: PhantomData };
+		(AtomicPtr::new(node), guard)
+	}
+}
+
+/// The guard returned by [`Rcu::into_parts`].
+///
+/// Dropping it releases the ref the [`Rcu`] had on its value, and frees
+/// the value if that was the last one.
+#[must_use = "dropping the guard frees the value right away"]
+pub struct RcuDropGuard<T> {
+	node: *mut Inner<T>,
+	_marker: PhantomData<Box<Inner<T>>>,
+}
+
+impl<T> RcuDropGuard<T> {
+	/// Get the node this guard frees.
+	#[must_use]
+	pub const fn as_ptr(&self) -> *mut Inner<T> {
+		self.node
+	}
+}
+
+impl<T> fmt::Debug for RcuDropGuard<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("RcuDropGuard").field(&self.node).finish()
+	}
+}
+
+impl<T> Drop for RcuDropGuard<T> {
+	fn drop(&mut self) {
+		unsafe {
+			if Refs::release_ref(&raw const (*self.node).refs) {
+				Inner::free(self.node);
+			}
+		}
+	}
+}
+
+unsafe impl<T: Send + Sync> Sync for RcuDropGuard<T> {}
+unsafe impl<T: Send + Sync> Send for RcuDropGuard<T> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::sync::Arc;
+
+	use portable_atomic::Ordering;
+
+	#[test]
+	fn test_into_parts() {
+		let value = Arc::new(1);
+		let rcu = Rcu::new(Arc::clone(&value));
+
+		let (ptr, guard) = unsafe { Rcu::into_parts(rcu) };
+		let node = ptr.load(Ordering::Relaxed);
+		assert_eq!(node, guard.as_ptr());
+		assert_eq!(Arc::strong_count(&value), 2);
+
+		drop(guard);
+		assert_eq!(Arc::strong_count(&value), 1);
+	}
+}
@@ -1,28 +1,238 @@
-use core::{marker::PhantomData, ops::Deref};
+use core::{
+	borrow::Borrow,
+	cmp::Ordering as CmpOrdering,
+	fmt,
+	hash::{Hash, Hasher},
+	marker::PhantomData,
+	mem::{self, ManuallyDrop},
+	ops::Deref,
+	ptr,
+};
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String};
 
-use portable_atomic::{AtomicPtr, Ordering};
+use portable_atomic::{
+	fence, AtomicBool, AtomicPtr, AtomicU64, Ordering,
+};
 
-use crate::refs::Refs;
-
-struct Inner<T> {
-	/// The number of active references to the specific `Inner`.
-	refs: Refs,
-	/// The data.
-	data: T,
-}
+use crate::observer::Observer;
+use crate::reclaim::{self, Inner, Reclaim, RefCounting};
+use crate::refs::RefsOverflow;
+#[cfg(feature = "std")]
+use crate::watch::{Watch, Watchers};
 
 /// The RCU implementation.
-pub struct Rcu<T> {
+///
+/// `T` must be [`Sized`], so `Rcu<dyn Trait>` and `Rcu<[T]>` cannot be
+/// built. To share an unsized value like a trait object or a slice, store
+/// it behind a [`Box`], as in `Rcu<Box<dyn Trait>>`. Guards to such values
+/// can then be [mapped] to the unsized value itself.
+///
+/// `R` is the strategy used to decide when values that have been replaced
+/// can be freed. See the [`reclaim`] module.
+///
+/// An [`Rcu`] can only be shared between threads if `T` is both [`Send`]
+/// and [`Sync`]:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+///
+/// use rcurs::Rcu;
+///
+/// fn assert_sync<T: Sync>(_: &T) {}
+///
+/// assert_sync(&Rcu::new(Rc::new(0)));
+/// ```
+///
+/// [`Box`]: alloc::boxed::Box
+/// [mapped]: Guard::map
+/// [`reclaim`]: crate::reclaim
+pub struct Rcu<T, R: Reclaim = RefCounting> {
 	ptr: AtomicPtr<Inner<T>>,
+	reclaim: R,
+	/// The number of times the value has been replaced.
+	version: AtomicU64,
+	/// Whether a closure panicked while computing a new value.
+	poisoned: AtomicBool,
+	observer: Option<Box<dyn Observer<T> + Send + Sync>>,
+	#[cfg(feature = "std")]
+	pub(crate) watchers: Watchers,
 }
 
 impl<T> Rcu<T> {
 	/// Create a new [`Rcu`] with an initial value of `data`.
 	pub fn new(data: T) -> Self {
-		let ptr = alloc(Inner { data, refs: Refs::one() });
-		Self { ptr: AtomicPtr::new(ptr) }
+		Self::with_reclaim(data, RefCounting::default())
+	}
+
+	/// Create a new [`Rcu`] with an initial value that is already on the
+	/// heap.
+	///
+	/// The box cannot be reused as is, because the value lives next to
+	/// its ref count, which is cache line aligned. But the value is moved
+	/// directly from `data` to its new allocation without ever being
+	/// placed on the stack. This avoids both the copies and the stack
+	/// usage of `Rcu::new(*data)` for large values.
+	#[must_use]
+	pub fn from_box(data: Box<T>) -> Self {
+		Self::from_node(
+			reclaim::alloc_boxed(data),
+			RefCounting::default(),
+		)
+	}
+
+	/// Create a new [`Rcu`] with an initial value of `data` that calls
+	/// `observer` every time the value is replaced.
+	///
+	/// See: [`Observer`].
+	pub fn with_observer<O>(data: T, observer: O) -> Self
+	where
+		O: Observer<T> + Send + Sync + 'static,
+	{
+		let mut this = Self::new(data);
+		this.observer = Some(Box::new(observer));
+		this
+	}
+
+	/// Take the value out of the [`Rcu`].
+	///
+	/// This succeeds only if there are no guards referencing the current
+	/// value. Otherwise, the [`Rcu`] is returned back unchanged.
+	///
+	/// # Errors
+	///
+	/// Returns `Err(self)` if the current value is still referenced by
+	/// a guard.
+	pub fn try_into_inner(self) -> Result<T, Self> {
+		let inner = self.ptr.load(Ordering::Relaxed);
+
+		if unsafe { (*inner).refs.count() } != 1 {
+			return Err(self);
+		}
+
+		// Synchronize with the release of the other refs so we see all
+		// accesses to `data` made through them.
+		fence(Ordering::Acquire);
+
+		Ok(unsafe { reclaim::into_data(self.into_node()) })
+	}
+
+	/// Get the value inside the [`Rcu`], unless it has too many guards.
+	///
+	/// This is the same as [`get`], except that it returns an error
+	/// instead of panicking when the ref count of the value would
+	/// overflow.
+	///
+	/// # Errors
+	///
+	/// Returns [`RefsOverflow`] if the value has too many guards.
+	///
+	/// [`get`]: Self::get
+	pub fn try_get(&self) -> Result<Guard<'_, T>, RefsOverflow> {
+		self.reclaim.try_protect(&self.ptr).map(
+			|(node, token)| unsafe {
+				Guard::from_node::<RefCounting>(node, token)
+			},
+		)
+	}
+
+	/// Get a mutable reference to the value inside the [`Rcu`].
+	///
+	/// This succeeds only if there are no guards referencing the current
+	/// value. Otherwise, it returns [`None`].
+	pub fn get_mut(&mut self) -> Option<&mut T> {
+		let inner = *self.ptr.get_mut();
+
+		if unsafe { (*inner).refs.count() } != 1 {
+			return None;
+		}
+
+		// Synchronize with the release of the other refs so we see all
+		// accesses to `data` made through them.
+		fence(Ordering::Acquire);
+
+		Some(unsafe { &mut (*inner).data })
+	}
+
+	/// Wait until no guard references the current value anymore, or until
+	/// the value is replaced.
+	///
+	/// Either way, every reader that got the current value before this
+	/// call has dropped its guard or will see a newer value the next time
+	/// it calls [`get`].
+	///
+	/// This function blocks execution. It never returns if the calling
+	/// thread holds a guard to the current value itself.
+	///
+	/// [`get`]: Self::get
+	pub fn synchronize(&self) {
+		let (node, _guard) = self.load_node();
+
+		// Discount the ref of the `Rcu` and the one of `_guard`.
+		while self.ptr.load(Ordering::Acquire) == node
+			&& unsafe { (*node).refs.count() } > 2
+		{
+			#[cfg(feature = "std")]
+			std::thread::yield_now();
+			#[cfg(not(feature = "std"))]
+			core::hint::spin_loop();
+		}
+
+		fence(Ordering::Acquire);
+	}
+
+	/// Get the number of guards that reference the current value.
+	///
+	/// This is only a snapshot. Guards might be created or dropped, and
+	/// the value might be replaced, by the time this returns. Use it for
+	/// metrics and debug assertions, not for synchronization.
+	pub fn reader_count(&self) -> usize {
+		let (node, _guard) = self.load_node();
+
+		// Discount the ref of the `Rcu` and the one of `_guard`.
+		unsafe { (*node).refs.count() }.saturating_sub(2)
+	}
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Create a new [`Rcu`] with an initial value of `data` that uses
+	/// `reclaim` to free replaced values.
+	pub fn with_reclaim(data: T, reclaim: R) -> Self {
+		Self::from_node(reclaim::alloc(data), reclaim)
+	}
+
+	/// Take the value out of the [`Rcu`].
+	///
+	/// Unlike [`try_into_inner`], this cannot fail. Guards borrow the
+	/// [`Rcu`], so owning it means that none of them can be in use
+	/// anymore. This makes it possible to share an [`Rcu`] with scoped
+	/// threads and get the final value back once they are done.
+	///
+	/// [`try_into_inner`]: Rcu::try_into_inner
+	pub fn take(this: Self) -> T {
+		unsafe { reclaim::into_data(this.into_node()) }
+	}
+
+	/// Consume the [`Rcu`] and leak its current value, returning a
+	/// reference to it that lives for the rest of the program.
+	///
+	/// This is the [`Rcu`] analogue of [`Box::leak`]. It is useful for
+	/// values that are set up once and then only read, or that have to be
+	/// handed to code that keeps a pointer to them forever. The value is
+	/// never dropped and its memory is never freed.
+	///
+	/// Values that have been replaced earlier are still reclaimed as
+	/// usual.
+	///
+	/// [`Box::leak`]: alloc::boxed::Box::leak
+	#[must_use]
+	pub fn leak(this: Self) -> &'static T
+	where
+		T: 'static,
+	{
+		// Guards borrow the `Rcu`, so none can be alive. The node is
+		// never freed, so its ref count does not matter anymore.
+		unsafe { &(*this.into_node()).data }
 	}
 
 	/// Update the value inside the [`Rcu`] and return the old one.
@@ -31,14 +241,251 @@ impl<T> Rcu<T> {
 	/// [`update`] returns. You must make sure that when calling this function
 	/// the new value is fully initialized beforehand.
 	///
-	/// This function does _not_ block execution.
+	/// With the default [`RefCounting`] strategy, this function spins
+	/// until every [`get`] call that is taking its ref at the same time
+	/// has done so. That window is short, but the wait has no upper
+	/// bound. A steady stream of overlapping [`get`] calls can keep the
+	/// writer waiting. So can a reader that is preempted inside the window.
+	/// Calling this from an interrupt handler that interrupted a [`get`]
+	/// on the same core deadlocks. See [`RefCounting`] for details.
 	///
 	/// [`get`]: Self::get
 	/// [`update`]: Self::update
 	pub fn update(&self, new: T) {
-		let new_ptr = alloc(Inner { data: new, refs: Refs::one() });
-		let old_ptr = self.ptr.swap(new_ptr, Ordering::Relaxed);
-		unsafe { drop_inner(old_ptr) };
+		drop(self.replace(new));
+	}
+
+	/// Update the value inside the [`Rcu`] and return a guard to the old
+	/// one.
+	///
+	/// This is the same as [`update`] except that the old value is kept
+	/// alive for as long as the returned [`OldGuard`] lives, even if all
+	/// other guards referencing it are dropped. Holding onto the returned
+	/// guard thus keeps both the old and the new value in memory. Drop it
+	/// as soon as you are done with the old value.
+	///
+	/// [`update`]: Self::update
+	#[doc(alias = "swap")]
+	pub fn replace(&self, new: T) -> OldGuard<'_, T> {
+		unsafe { self.install(reclaim::alloc(new)) }
+	}
+
+	/// Update the value inside the [`Rcu`] and return a guard to the old
+	/// one, unless there is no room to retire the old one.
+	///
+	/// This is the same as [`replace`], except that it hands `new` back
+	/// instead of panicking if the [`Reclaim`] strategy has no room left.
+	/// Of the strategies in this crate, only [`Deferred`] ever runs out of
+	/// room, once its queue is full.
+	///
+	/// # Errors
+	///
+	/// Returns `Err(new)` if there is no room to retire the old value.
+	///
+	/// [`replace`]: Self::replace
+	/// [`Deferred`]: crate::reclaim::Deferred
+	pub fn try_replace(&self, new: T) -> Result<OldGuard<'_, T>, T> {
+		unsafe { self.try_install(reclaim::alloc(new)) }
+			.map_err(|x| unsafe { reclaim::into_data(x) })
+	}
+
+	/// Update the value inside the [`Rcu`] unless someone else updates it
+	/// at the same time.
+	///
+	/// Returns `true` if `new` was installed. Returns `false` and drops
+	/// `new` if the value was replaced between loading it and swapping in
+	/// `new`. Unlike [`update_with`], this never retries.
+	///
+	/// [`update_with`]: Self::update_with
+	pub fn try_update(&self, new: T) -> bool {
+		let (node, _guard) = self.load_node();
+		self.compare_and_replace(node, new).is_ok()
+	}
+
+	/// Update the value inside the [`Rcu`] to the [`Default`] value of
+	/// `T`.
+	///
+	/// This is the same as `update(T::default())`. It always writes, even
+	/// if the current value already is the default one. Checking that and
+	/// skipping the write cannot be done atomically with respect to other
+	/// updates, see [`idempotent_update`].
+	///
+	/// [`idempotent_update`]: Self::idempotent_update
+	pub fn reset_to_default(&self)
+	where
+		T: Default,
+	{
+		self.update(T::default());
+	}
+
+	/// Update the value inside the [`Rcu`] unless it is already equal to
+	/// `new`.
+	///
+	/// Returns `false` without allocating if the current value is equal to
+	/// `new`. Otherwise, it [`update`]s the value and returns `true`.
+	///
+	/// The check is optimistic. The value might be updated by someone else
+	/// between the comparison and the update, in which case `new` replaces
+	/// that value even if it is equal. Use [`update_with`] if the
+	/// comparison needs to be against the value that is replaced.
+	///
+	/// [`update`]: Self::update
+	/// [`update_with`]: Self::update_with
+	pub fn idempotent_update(&self, new: T) -> bool
+	where
+		T: PartialEq,
+	{
+		if *self.get() == new {
+			return false;
+		}
+
+		self.update(new);
+		true
+	}
+
+	/// Update the value inside the [`Rcu`] if `predicate` returns `true`
+	/// for the current value.
+	///
+	/// Returns whether the value was updated. If it was not, `new` is
+	/// dropped.
+	///
+	/// The check is optimistic. The value might be updated by someone else
+	/// between calling `predicate` and the update, in which case `new`
+	/// replaces that value without it being checked. Use
+	/// [`compare_and_update`] or [`update_with`] if the check must hold
+	/// for the value that is replaced.
+	///
+	/// [`compare_and_update`]: Self::compare_and_update
+	/// [`update_with`]: Self::update_with
+	pub fn update_if<F>(&self, new: T, predicate: F) -> bool
+	where
+		F: FnOnce(&T) -> bool,
+	{
+		if !predicate(&self.get()) {
+			return false;
+		}
+
+		self.update(new);
+		true
+	}
+
+	/// Update the value inside the [`Rcu`] based on its current value.
+	///
+	/// `f` is called with the current value and must return the new one.
+	/// If the [`Rcu`] is [`update`]d by someone else while `f` is running,
+	/// the value `f` returned is discarded and `f` is called again with
+	/// the value that won the race.
+	///
+	/// Returns `false` if `f` returned a value equal to the current one,
+	/// in which case no write happens. Otherwise, it returns `true`.
+	///
+	/// If `f` panics, the [`Rcu`] is left unmodified and [poisoned].
+	///
+	/// This function does _not_ block execution, but it might call `f`
+	/// many times under heavy write contention.
+	///
+	/// [poisoned]: Self::is_poisoned
+	///
+	/// [`update`]: Self::update
+	pub fn update_with<F>(&self, mut f: F) -> bool
+	where
+		T: PartialEq,
+		F: FnMut(&T) -> T,
+	{
+		loop {
+			let (node, current) = self.load_node();
+
+			let new = self.poison_on_panic(|| f(&current));
+			if new == *current {
+				return false;
+			}
+
+			if self.compare_and_replace(node, new).is_ok() {
+				return true;
+			}
+		}
+	}
+
+	/// Update the value inside the [`Rcu`] based on its current value,
+	/// giving up after `max_retries` lost races.
+	///
+	/// This is the same as [`update_with`], except that `f` is called at
+	/// most `max_retries + 1` times, and that the new value is always
+	/// written. Under heavy write contention, [`update_with`] could keep
+	/// losing the race forever. This bounds the time it takes, so the
+	/// caller can fall back to something else, like a write lock.
+	///
+	/// # Errors
+	///
+	/// Returns [`TryUpdateError::TooManyRetries`] if every attempt lost
+	/// the race to another update. The [`Rcu`] is left unmodified by this
+	/// call in that case.
+	///
+	/// [`update_with`]: Self::update_with
+	pub fn try_update_with<F>(
+		&self,
+		max_retries: usize,
+		mut f: F,
+	) -> Result<(), TryUpdateError>
+	where
+		F: FnMut(&T) -> T,
+	{
+		for _ in 0..=max_retries {
+			let (node, current) = self.load_node();
+
+			let new = self.poison_on_panic(|| f(&current));
+			if self.compare_and_replace(node, new).is_ok() {
+				return Ok(());
+			}
+		}
+
+		Err(TryUpdateError::TooManyRetries {
+			attempts: max_retries.saturating_add(1),
+		})
+	}
+
+	/// Update the value inside the [`Rcu`] only if it is equal to
+	/// `expected`.
+	///
+	/// This is a compare-and-swap on the _value_ inside the [`Rcu`], not on
+	/// a pointer. The comparison is done with [`PartialEq`].
+	///
+	/// If the [`Rcu`] is [`update`]d by someone else between comparing the
+	/// current value and installing `new`, the comparison is repeated with
+	/// the value that won the race.
+	///
+	/// This is _not_ lock-free in the presence of multiple writers. A
+	/// writer that keeps losing the race has to keep comparing. Callers
+	/// that want to apply a change on top of whatever the current value is
+	/// must loop themselves, or use [`update_with`].
+	///
+	/// # Errors
+	///
+	/// Returns `Err(new)` if the current value is not equal to `expected`.
+	///
+	/// [`update`]: Self::update
+	/// [`update_with`]: Self::update_with
+	pub fn compare_and_update(
+		&self,
+		expected: &T,
+		new: T,
+	) -> Result<(), T>
+	where
+		T: PartialEq,
+	{
+		let mut new = new;
+
+		loop {
+			let (node, current) = self.load_node();
+			if *current != *expected {
+				return Err(new);
+			}
+
+			match self.compare_and_replace(node, new) {
+				Ok(_) => return Ok(()),
+				Err(x) => new = x,
+			}
+		}
 	}
 
 	/// Get the value inside the [`Rcu`].
@@ -56,118 +503,1791 @@ impl<T> Rcu<T> {
 	///
 	/// [`update`]: Self::update
 	pub fn get(&self) -> Guard<'_, T> {
-		let inner = self.ptr.load(Ordering::Relaxed).cast_const();
-		unsafe { (*inner).refs.take_ref() };
-		Guard { _marker: PhantomData, inner }
+		self.load_node().1
 	}
-}
 
-impl<T> Drop for Rcu<T> {
-	fn drop(&mut self) {
-		unsafe { drop_inner(self.ptr.load(Ordering::Relaxed)) };
+	/// Call `f` with the value inside the [`Rcu`].
+	///
+	/// This is the same as calling [`get`] and passing the guard to `f`,
+	/// except that the guard cannot outlive the call. Prefer it for short
+	/// reads, so the old value is not accidentally kept alive for longer
+	/// than needed.
+	///
+	/// This function does _not_ block execution.
+	///
+	/// [`get`]: Self::get
+	pub fn with<U, F>(&self, f: F) -> U
+	where
+		F: FnOnce(&T) -> U,
+	{
+		f(&self.get())
 	}
-}
 
-unsafe impl<T> Sync for Rcu<T> {}
-unsafe impl<T> Send for Rcu<T> {}
+	/// Get the value inside the [`Rcu`] if it satisfies `pred`.
+	///
+	/// `pred` is called with the same value that is returned, so the value
+	/// cannot change between checking and using it.
+	///
+	/// This function does _not_ block execution.
+	pub fn get_if<F>(&self, pred: F) -> Option<Guard<'_, T>>
+	where
+		F: FnOnce(&T) -> bool,
+	{
+		let guard = self.get();
+		pred(&guard).then_some(guard)
+	}
 
-/// The RAII guard returned by [`Rcu`].
-///
-/// See: [`Rcu::get`].
-pub struct Guard<'a, T> {
-	_marker: PhantomData<&'a ()>,
-	inner: *const Inner<T>,
-}
+	/// Update the value inside the [`Rcu`] by modifying a clone of it.
+	///
+	/// The current value is cloned, `f` modifies the clone in place and
+	/// the clone is then installed with [`update`]. If `f` panics, the
+	/// [`Rcu`] is left unmodified and [poisoned].
+	///
+	/// Unlike [`update_with`], concurrent updates are not retried. If
+	/// the [`Rcu`] is updated by someone else while `f` is running, that
+	/// update is overwritten.
+	///
+	/// [`update`]: Self::update
+	/// [`update_with`]: Self::update_with
+	/// [poisoned]: Self::is_poisoned
+	pub fn with_mut<F>(&self, f: F)
+	where
+		T: Clone,
+		F: FnOnce(&mut T),
+	{
+		let mut new = Guard::into_owned(self.get());
+		self.poison_on_panic(|| f(&mut new));
+		self.update(new);
+	}
 
-impl<'a, T> Deref for Guard<'a, T> {
-	type Target = T;
+	/// Check whether the [`Rcu`] is poisoned.
+	///
+	/// An [`Rcu`] is poisoned when the closure passed to [`update_with`],
+	/// [`try_update_with`] or [`with_mut`] panics. The value inside it is
+	/// _not_ affected, because the closure only ever works on a new value
+	/// that is never installed. But whatever the closure was supposed to
+	/// change did not happen, which other code might want to know about.
+	///
+	/// [`update_with`]: Self::update_with
+	/// [`try_update_with`]: Self::try_update_with
+	/// [`with_mut`]: Self::with_mut
+	pub fn is_poisoned(&self) -> bool {
+		self.poisoned.load(Ordering::Relaxed)
+	}
 
-	fn deref(&self) -> &Self::Target {
-		unsafe { &(*self.inner).data }
+	/// Clear the poisoned state of the [`Rcu`].
+	///
+	/// See: [`is_poisoned`](Self::is_poisoned).
+	pub fn clear_poison(&self) {
+		self.poisoned.store(false, Ordering::Relaxed);
 	}
-}
 
-impl<'a, T> Drop for Guard<'a, T> {
-	fn drop(&mut self) {
-		unsafe { drop_inner(self.inner.cast_mut()) };
+	/// Get the value inside the [`Rcu`] along with its version.
+	///
+	/// The version starts at `0` and is incremented every time the value
+	/// is replaced, by any of the update methods.
+	///
+	/// The version is read before the value, so it is a lower bound. The
+	/// value might be newer than the returned version says if an update
+	/// happened in between. Two calls that return the same version might
+	/// therefore still return different values. But a value is never older
+	/// than its version.
+	///
+	/// This function does _not_ block execution.
+	pub fn get_version(&self) -> (Guard<'_, T>, u64) {
+		let version = self.version.load(Ordering::Acquire);
+		(self.get(), version)
 	}
-}
 
-unsafe impl<T> Sync for Guard<'_, T> {}
-unsafe impl<T> Send for Guard<'_, T> {}
+	/// Wait until the value is replaced with one other than the one `guard`
+	/// references and get it.
+	///
+	/// Returns right away if the value has already been replaced. `guard`
+	/// must be a guard of this [`Rcu`] that has not been [mapped], otherwise
+	/// this might also return right away.
+	///
+	/// This function blocks execution. With the `std` feature, the thread
+	/// sleeps until an update arrives. Without it, it spins.
+	///
+	/// [mapped]: Guard::map
+	pub fn wait_changed(&self, guard: &Guard<'_, T>) -> Guard<'_, T> {
+		#[cfg(feature = "std")]
+		let watch = self.watch();
+
+		loop {
+			let current = self.get();
+			if Guard::as_ptr(&current) != Guard::as_ptr(guard) {
+				return current;
+			}
+			drop(current);
 
-/// Release a ref from `x` and drop it if there are no more refs.
-unsafe fn drop_inner<T>(x: *mut Inner<T>) {
-	if (*x).refs.release_ref() {
-		free(x);
+			#[cfg(feature = "std")]
+			drop(watch.changed());
+			#[cfg(not(feature = "std"))]
+			core::hint::spin_loop();
+		}
 	}
-}
 
-fn alloc<T>(x: T) -> *mut T {
-	Box::into_raw(Box::new(x))
-}
+	/// Get the number of times the value has been replaced.
+	///
+	/// This is the same counter [`get_version`] returns, without getting
+	/// the value. Compare it before and after some work to find out
+	/// whether the value changed in the meantime.
+	///
+	/// [`get_version`]: Self::get_version
+	pub fn generation(&self) -> u64 {
+		self.version.load(Ordering::Relaxed)
+	}
 
-unsafe fn free<T>(x: *mut T) {
-	drop(Box::from_raw(x));
-}
+	/// Watch the [`Rcu`] for changes.
+	///
+	/// The returned [`Watch`] is notified every time the value is
+	/// replaced, by any of the update methods.
+	#[cfg(feature = "std")]
+	pub fn watch(&self) -> Watch<'_, T, R> {
+		Watch::new(self)
+	}
 
-#[cfg(all(test, feature = "std"))]
-mod tests {
-	use super::*;
+	/// Get the reclamation strategy of the [`Rcu`].
+	pub const fn reclaim(&self) -> &R {
+		&self.reclaim
+	}
 
-	use std::thread::{scope, sleep};
-	use std::time::Duration;
+	/// Replace the node of the [`Rcu`] with `new` if it is `current`.
+	///
+	/// This is a plain compare-and-exchange on the pointer inside the
+	/// [`Rcu`], for building custom algorithms on top of it. It does not
+	/// retire the old node, bump the [`generation`], call the observer or
+	/// notify watchers.
+	///
+	/// Returns the previous node, like [`AtomicPtr::compare_exchange`].
+	///
+	/// # Errors
+	///
+	/// Returns `Err` with the node that is installed instead, if it is not
+	/// `current`. `new` is not installed then.
+	///
+	/// # Safety
+	///
+	/// - `new` must have been allocated with [`Inner::new`] and must not
+	///   be used by the caller after it has been installed.
+	/// - The caller must keep `current` protected while this runs, so that
+	///   its address cannot be reused by another node. The guard returned
+	///   along with it by [`load_node`] does that.
+	/// - On success, the caller owns the old node and must pass it to
+	///   [`Reclaim::retire`] of [`reclaim`], and release the returned token.
+	/// - `success` must be [`SeqCst`] for the strategies of this crate,
+	///   which rely on the pointer being swapped in a single total order
+	///   with their own accesses.
+	///
+	/// # Examples
+	///
+	/// Increment the value with a compare-and-exchange loop:
+	///
+	/// ```
+	/// use std::sync::atomic::Ordering;
+	///
+	/// use rcurs::reclaim::{Inner, Reclaim, RefCounting};
+	/// use rcurs::Rcu;
+	///
+	/// let rcu = Rcu::new(1);
+	///
+	/// loop {
+	///     let (current, guard) = rcu.load_node();
+	///     let new = Inner::new(*guard + 1);
+	///
+	///     let r = unsafe {
+	///         rcu.compare_exchange_ptr(
+	///             current,
+	///             new,
+	///             Ordering::SeqCst,
+	///             Ordering::Relaxed,
+	///         )
+	///     };
+	///
+	///     match r {
+	///         Ok(old) => {
+	///             unsafe {
+	///                 let token = rcu.reclaim().retire(old);
+	///                 RefCounting::release::<i32>(token);
+	///             }
+	///             break;
+	///         }
+	///         // `new` was never installed, so no reader can have seen it.
+	///         Err(_) => unsafe { Inner::free(new) },
+	///     }
+	/// }
+	///
+	/// assert_eq!(*rcu.get(), 2);
+	/// ```
+	///
+	/// [`generation`]: Self::generation
+	/// [`load_node`]: Self::load_node
+	/// [`reclaim`]: Self::reclaim
+	/// [`SeqCst`]: Ordering::SeqCst
+	pub unsafe fn compare_exchange_ptr(
+		&self,
+		current: *mut Inner<T>,
+		new: *mut Inner<T>,
+		success: Ordering,
+		failure: Ordering,
+	) -> Result<*mut Inner<T>, *mut Inner<T>> {
+		self.ptr.compare_exchange(current, new, success, failure)
+	}
 
-	type UserRcu = Rcu<User>;
+	/// Call `f` and poison the [`Rcu`] if it panics.
+	fn poison_on_panic<U>(&self, f: impl FnOnce() -> U) -> U {
+		struct Poison<'a>(&'a AtomicBool);
 
-	#[derive(Debug, PartialEq, Eq)]
-	struct User {
-		id: i32,
-		name: &'static str,
-	}
+		impl Drop for Poison<'_> {
+			fn drop(&mut self) {
+				self.0.store(true, Ordering::Relaxed);
+			}
+		}
 
-	impl User {
-		const A: Self = Self { id: 1, name: "user 1" };
+		// Only dropped if `f` unwinds.
+		let poison = Poison(&self.poisoned);
+		let r = f();
+		mem::forget(poison);
+		r
+	}
 
-		const B: Self = Self { id: 2, name: "user 2" };
+	/// Create an [`Rcu`] whose current value is `node`.
+	fn from_node(node: *mut Inner<T>, reclaim: R) -> Self {
+		Self {
+			ptr: AtomicPtr::new(node),
+			reclaim,
+			version: AtomicU64::new(0),
+			poisoned: AtomicBool::new(false),
+			observer: None,
+			#[cfg(feature = "std")]
+			watchers: Watchers::new(),
+		}
 	}
 
-	#[test]
-	fn test_rcu() {
-		fn routine<'a>(
-			start_in: u64,
-			run_for: u64,
-			rcu: &'a UserRcu,
-			expected: User,
-		) -> impl FnOnce() + Send + 'a {
-			const CHECK_COUNT: u32 = 5;
+	/// Drop everything but the current node and return it.
+	///
+	/// The caller is responsible for freeing the node.
+	pub(crate) fn into_node(self) -> *mut Inner<T> {
+		let mut this = ManuallyDrop::new(self);
+		let node = *this.ptr.get_mut();
 
-			move || {
-				sleep(Duration::from_secs(start_in));
+		unsafe {
+			ptr::drop_in_place(&raw mut this.reclaim);
+			ptr::drop_in_place(&raw mut this.observer);
+			#[cfg(feature = "std")]
+			ptr::drop_in_place(&raw mut this.watchers);
+		}
 
-				let user = rcu.get();
+		node
+	}
 
-				let t = Duration::from_secs(run_for) / CHECK_COUNT;
-				for _ in 0..CHECK_COUNT {
-					sleep(t);
-					assert_eq!(*user, expected);
-				}
-			}
+	/// Swap in `new_ptr` unconditionally and retire the old node.
+	///
+	/// Panics if there is no room to retire the old node.
+	///
+	/// # Safety
+	///
+	/// `new_ptr` must be a fresh node that is not used anywhere else.
+	pub(crate) unsafe fn install(
+		&self,
+		new_ptr: *mut Inner<T>,
+	) -> OldGuard<'_, T> {
+		match self.try_install(new_ptr) {
+			Ok(x) => x,
+			Err(x) => {
+				drop(reclaim::into_data(x));
+				panic_no_room()
+			},
 		}
+	}
 
-		let user = Rcu::new(User::A);
+	/// Same as [`install`], but hands `new_ptr` back if there is no room
+	/// to retire the old node.
+	///
+	/// # Safety
+	///
+	/// See [`install`].
+	///
+	/// [`install`]: Self::install
+	unsafe fn try_install(
+		&self,
+		new_ptr: *mut Inner<T>,
+	) -> Result<OldGuard<'_, T>, *mut Inner<T>> {
+		if !self.reclaim.reserve() {
+			return Err(new_ptr);
+		}
 
-		scope(|scope| {
-			scope.spawn(routine(0, 10, &user, User::A));
-			scope.spawn(routine(4, 15, &user, User::A));
+		let new = self.protect_new(new_ptr);
 
-			// Any readers past t=5 must see User::B
-			scope.spawn(routine(6, 4, &user, User::B));
+		// Release publishes the initialization of `new_ptr` to readers
+		// that load it. `SeqCst` additionally orders the swap with the
+		// `Reclaim` strategy, see `RefCounting::retire`.
+		let old_ptr = self.ptr.swap(new_ptr, Ordering::SeqCst);
+		Ok(unsafe { self.retire(old_ptr, new) })
+	}
+
+	/// Check whether `node` is the current node of the [`Rcu`].
+	///
+	/// The caller must keep `node` protected, so that its address cannot
+	/// be reused by another node.
+	pub(crate) fn is_current(&self, node: *mut Inner<T>) -> bool {
+		// Nothing is read through the loaded pointer, so there is nothing
+		// to synchronize with.
+		self.ptr.load(Ordering::Relaxed) == node
+	}
+
+	/// Get a guard to the current value along with the node it lives in.
+	///
+	/// The node stays protected for as long as the guard is alive, so it
+	/// can be passed as `current` to [`compare_exchange_ptr`].
+	///
+	/// [`compare_exchange_ptr`]: Self::compare_exchange_ptr
+	#[must_use]
+	pub fn load_node(&self) -> (*mut Inner<T>, Guard<'_, T>) {
+		let (node, token) = self.reclaim.protect(&self.ptr);
+		let guard = unsafe { Guard::from_node::<R>(node, token) };
+		(node, guard)
+	}
+
+	/// Replace the value of the [`Rcu`] with `new`, but only if it is still
+	/// stored in `current`.
+	///
+	/// Returns `new` back if the value was replaced by someone else in the
+	/// meantime.
+	///
+	/// The caller must keep `current` protected while this runs, so that
+	/// it cannot be freed and its address cannot be reused while we
+	/// compare against it.
+	///
+	/// Panics if there is no room to retire the old value.
+	fn compare_and_replace(
+		&self,
+		current: *mut Inner<T>,
+		new: T,
+	) -> Result<OldGuard<'_, T>, T> {
+		if !self.reclaim.reserve() {
+			panic_no_room();
+		}
+
+		let new_ptr = reclaim::alloc(new);
+		let new = self.protect_new(new_ptr);
+
+		let r = self.ptr.compare_exchange(
+			current,
+			new_ptr,
+			Ordering::SeqCst,
+			Ordering::Relaxed,
+		);
+
+		// On failure, `new` is dropped along with the first closure, so it
+		// no longer protects `new_ptr` when that is freed.
+		r.map(|old_ptr| unsafe { self.retire(old_ptr, new) })
+			.map_err(|_| {
+				self.reclaim.unreserve();
+				unsafe { reclaim::into_data(new_ptr) }
+			})
+	}
+
+	/// Protect `new` before it is installed, if it needs to be passed to
+	/// the observer afterwards.
+	///
+	/// Once `new` is installed, a concurrent update might replace and free
+	/// it at any time.
+	fn protect_new(
+		&self,
+		new: *mut Inner<T>,
+	) -> Option<Guard<'_, T>> {
+		self.observer.as_ref()?;
+
+		let (node, token) =
+			self.reclaim.protect(&AtomicPtr::new(new));
+		Some(unsafe { Guard::from_node::<R>(node, token) })
+	}
+
+	/// Hand `old` over to the reclamation strategy after it has been
+	/// swapped out of `ptr`.
+	///
+	/// `new` is the value that replaced `old`, as returned by
+	/// [`protect_new`].
+	///
+	/// # Safety
+	///
+	/// `old` must have been swapped out of `ptr` by the caller.
+	///
+	/// [`protect_new`]: Self::protect_new
+	unsafe fn retire(
+		&self,
+		old: *mut Inner<T>,
+		new: Option<Guard<'_, T>>,
+	) -> OldGuard<'_, T> {
+		self.version.fetch_add(1, Ordering::Release);
+
+		let token = self.reclaim.retire(old);
+		let old = OldGuard {
+			guard: Guard::from_node::<R>(old, token),
+			try_unwrap: R::try_unwrap::<T>,
+		};
+
+		if let (Some(observer), Some(new)) = (&self.observer, new) {
+			observer.on_update(&old, &new);
+		}
+
+		#[cfg(feature = "std")]
+		self.watchers.notify();
+
+		old
+	}
+}
+
+impl<T, R: Reclaim> Rcu<Option<T>, R> {
+	/// Get the value inside the [`Rcu`] if there is one.
+	///
+	/// The value is _not_ removed from the [`Rcu`]. Use [`clear_option`]
+	/// for that.
+	///
+	/// [`clear_option`]: Self::clear_option
+	pub fn take_option(&self) -> Option<Guard<'_, T>> {
+		let guard = self.get();
+		// The guard keeps the value from changing between the check and
+		// the map.
+		// `T` is `Send` and `Sync` exactly when `Option<T>` is.
+		guard.is_some().then(|| unsafe {
+			Guard::map_unchecked(guard, |x| x.as_ref().unwrap())
+		})
+	}
+
+	/// Update the value inside the [`Rcu`] to `Some(val)`.
+	///
+	/// See: [`update`](Self::update).
+	pub fn put_option(&self, val: T) {
+		self.update(Some(val));
+	}
+
+	/// Update the value inside the [`Rcu`] to `None`.
+	///
+	/// See: [`update`](Self::update).
+	pub fn clear_option(&self) {
+		self.update(None);
+	}
+}
+
+impl<R: Reclaim> Rcu<String, R> {
+	/// Append `s` to the string inside the [`Rcu`].
+	///
+	/// The string is cloned and the clone is appended to. Appends
+	/// from many threads at once are all kept.
+	///
+	/// See: [`update_with`](Self::update_with).
+	pub fn push_str(&self, s: &str) {
+		self.update_with(|old| {
+			let mut new = String::with_capacity(old.len() + s.len());
+			new.push_str(old);
+			new.push_str(s);
+			new
+		});
+	}
+
+	/// Update the string inside the [`Rcu`] to an empty one.
+	///
+	/// See: [`update`](Self::update).
+	pub fn clear(&self) {
+		self.update(String::new());
+	}
+}
+
+/// Create a new, independent [`Rcu`] with a clone of the current value.
+///
+/// This clones the _value_, not the handle. Updates to one [`Rcu`] are not
+/// visible through the other. To share a single [`Rcu`] between threads,
+/// use a reference to it or wrap it in an `Arc`.
+impl<T: Clone, R: Reclaim + Default> Clone for Rcu<T, R> {
+	fn clone(&self) -> Self {
+		Self::with_reclaim(T::clone(&self.get()), R::default())
+	}
+}
+
+impl<T: Default, R: Reclaim + Default> Default for Rcu<T, R> {
+	fn default() -> Self {
+		Self::with_reclaim(T::default(), R::default())
+	}
+}
+
+impl<T, R: Reclaim + Default> From<T> for Rcu<T, R> {
+	fn from(data: T) -> Self {
+		Self::with_reclaim(data, R::default())
+	}
+}
+
+/// Compare the current values of both [`Rcu`]s.
+///
+/// The values are read one after the other, so the result might be stale
+/// by the time it is returned if either [`Rcu`] is updated concurrently.
+impl<T: PartialEq, R: Reclaim> PartialEq for Rcu<T, R> {
+	fn eq(&self, other: &Self) -> bool {
+		*self.get() == *other.get()
+	}
+}
+
+impl<T: Eq, R: Reclaim> Eq for Rcu<T, R> {}
+
+impl<T: PartialEq, R: Reclaim> PartialEq<Guard<'_, T>> for Rcu<T, R> {
+	fn eq(&self, other: &Guard<'_, T>) -> bool {
+		*self.get() == **other
+	}
+}
+
+/// Compare the current values of both [`Rcu`]s.
+///
+/// Like [`PartialEq`], this compares the values at the time of the call.
+/// The ordering of two [`Rcu`]s changes whenever either is updated, so an
+/// [`Rcu`] must not be used as the key of a sorted map or set while it can
+/// still be updated. Prefer ordering [`Guard`]s, whose values never change.
+impl<T: PartialOrd, R: Reclaim> PartialOrd for Rcu<T, R> {
+	fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+		T::partial_cmp(&self.get(), &other.get())
+	}
+}
+
+impl<T: Ord, R: Reclaim> Ord for Rcu<T, R> {
+	fn cmp(&self, other: &Self) -> CmpOrdering {
+		T::cmp(&self.get(), &other.get())
+	}
+}
+
+/// Hash the current value of the [`Rcu`].
+///
+/// The hash changes whenever the value is updated. An [`Rcu`] must thus not
+/// be used as the key of a map or set while it can still be updated.
+/// Prefer hashing [`Guard`]s, which always hash to the same value.
+impl<T: Hash, R: Reclaim> Hash for Rcu<T, R> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.get().hash(state);
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug for Rcu<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Rcu").field("value", &*self.get()).finish()
+	}
+}
+
+impl<T: fmt::Display, R: Reclaim> fmt::Display for Rcu<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&*self.get(), f)
+	}
+}
+
+/// Format the address of the current value.
+///
+/// This is the same address the [`fmt::Pointer`] impl of a [`Guard`] to
+/// the current value formats.
+impl<T, R: Reclaim> fmt::Pointer for Rcu<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// The value might be freed by a concurrent update, so only compute
+		// its address without dereferencing anything.
+		let data = self
+			.ptr
+			.load(Ordering::Relaxed)
+			.wrapping_byte_add(mem::offset_of!(Inner<T>, data))
+			.cast::<T>();
+
+		fmt::Pointer::fmt(&data, f)
+	}
+}
+
+impl<T, R: Reclaim> Drop for Rcu<T, R> {
+	fn drop(&mut self) {
+		// Nothing can load `ptr` anymore, so this is the same as swapping
+		// it out. If there is no room, `retire` falls back to something
+		// slower, but no update can race with it anymore.
+		self.reclaim.reserve();
+		unsafe {
+			let token = self.reclaim.retire(*self.ptr.get_mut());
+			R::release::<T>(token);
+		}
+	}
+}
+
+#[cold]
+#[inline(never)]
+fn panic_no_room() -> ! {
+	panic!("no room to retire the old value")
+}
+
+// Readers on other threads get `&T`, and updates on other threads drop
+// values that were created elsewhere.
+unsafe impl<T: Send + Sync, R: Reclaim + Sync> Sync for Rcu<T, R> {}
+unsafe impl<T: Send, R: Reclaim + Send> Send for Rcu<T, R> {}
+
+/// The error returned by [`Rcu::try_update_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TryUpdateError {
+	/// Another update won the race on every attempt.
+	TooManyRetries {
+		/// The number of times the update was attempted.
+		attempts: usize,
+	},
+}
+
+impl fmt::Display for TryUpdateError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::TooManyRetries { attempts } => write!(
+				f,
+				"update lost the race {attempts} times in a row"
+			),
+		}
+	}
+}
+
+impl core::error::Error for TryUpdateError {}
+
+/// The RAII guard returned by [`Rcu`].
+///
+/// See: [`Rcu::get`].
+pub struct Guard<'a, T: ?Sized> {
+	_marker: PhantomData<&'a ()>,
+	/// Points somewhere inside the data of the node `token` protects.
+	data: *const T,
+	/// The token returned by the reclamation strategy. Its type is erased
+	/// so that guards do not need to carry around the strategy, nor the
+	/// type of the whole value when mapped.
+	token: *const (),
+	/// The functions of the strategy that `token` belongs to.
+	vtable: &'static Vtable,
+}
+
+/// The functions of a [`Reclaim`] strategy for the type-erased tokens of
+/// [`Guard`]s.
+struct Vtable {
+	release: unsafe fn(*const ()),
+	reprotect: unsafe fn(*const ()) -> *const (),
+}
+
+impl<'a, T> Guard<'a, T> {
+	/// Create a guard for the data of `node`, which `token` protects.
+	const unsafe fn from_node<R: Reclaim>(
+		node: *const Inner<T>,
+		token: *const (),
+	) -> Self {
+		Self {
+			_marker: PhantomData,
+			data: Inner::data(node),
+			token,
+			vtable: const {
+				&Vtable {
+					release: R::release::<T>,
+					reprotect: R::reprotect::<T>,
+				}
+			},
+		}
+	}
+
+	/// Create a new guard from a pointer returned by [`Guard::as_ptr`].
+	///
+	/// This is meant for pointers that went through FFI and need to be
+	/// turned back into a guard. The new guard references the same value
+	/// as the original one, independently of it.
+	///
+	/// # Safety
+	///
+	/// `ptr` must have been returned by [`Guard::as_ptr`] for a guard of
+	/// `rcu` that is still live, and has not been [mapped].
+	///
+	/// [mapped]: Guard::map
+	pub unsafe fn from_ptr(ptr: *const T, rcu: &'a Rcu<T>) -> Self {
+		let _ = rcu;
+
+		let node = ptr
+			.byte_sub(mem::offset_of!(Inner<T>, data))
+			.cast::<Inner<T>>();
+		(*node).refs.take_ref();
+
+		Self::from_node::<RefCounting>(node, node.cast())
+	}
+}
+
+impl<'a, T: ?Sized> Guard<'a, T> {
+	/// Make a new guard for a component of the guarded value.
+	///
+	/// The new guard keeps the whole value alive, not only the component
+	/// returned by `f`. Whichever guard is dropped last might drop the
+	/// whole value, on whatever thread it was sent to, so only guards of
+	/// values that are [`Send`] and [`Sync`] can be mapped:
+	///
+	/// ```compile_fail
+	/// use std::rc::Rc;
+	///
+	/// use rcurs::{Guard, Rcu};
+	///
+	/// let rcu = Rcu::new((Rc::new(0), 1));
+	/// let one = Guard::map(rcu.get(), |x| &x.1);
+	/// ```
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::map(...)`. A method would interfere with methods of the
+	/// same name on the contents of the guard.
+	pub fn map<U: ?Sized, F>(guard: Self, f: F) -> Guard<'a, U>
+	where
+		T: Send + Sync,
+		F: FnOnce(&T) -> &U,
+	{
+		unsafe { Self::map_unchecked(guard, f) }
+	}
+
+	/// Same as [`map`], for any `T`.
+	///
+	/// # Safety
+	///
+	/// `U` may only be [`Send`] and [`Sync`] if `T` is. Otherwise the new
+	/// guard could be used to drop `T` on another thread.
+	///
+	/// [`map`]: Guard::map
+	pub(crate) unsafe fn map_unchecked<U: ?Sized, F>(
+		guard: Self,
+		f: F,
+	) -> Guard<'a, U>
+	where
+		F: FnOnce(&T) -> &U,
+	{
+		let data: *const U = f(&guard);
+
+		// The token of `guard` is handed over to the new guard.
+		let guard = ManuallyDrop::new(guard);
+
+		Guard {
+			_marker: PhantomData,
+			data,
+			token: guard.token,
+			vtable: guard.vtable,
+		}
+	}
+
+	/// Make two new guards for two components of the guarded value.
+	///
+	/// This is the same as [`map`], but for two components at once. Both
+	/// new guards keep the whole value alive, independently of each other.
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::split(...)`. A method would interfere with methods of the
+	/// same name on the contents of the guard.
+	///
+	/// [`map`]: Guard::map
+	pub fn split<U: ?Sized, V: ?Sized, F>(
+		guard: Self,
+		f: F,
+	) -> (Guard<'a, U>, Guard<'a, V>)
+	where
+		T: Send + Sync,
+		F: FnOnce(&T) -> (&U, &V),
+	{
+		let (u, v) = f(&guard);
+		let (u, v): (*const U, *const V) = (u, v);
+
+		// The token of `guard` is handed over to the first guard. The second
+		// one gets a new token of its own.
+		let guard = ManuallyDrop::new(guard);
+		let token = unsafe { (guard.vtable.reprotect)(guard.token) };
+
+		(
+			Guard {
+				_marker: PhantomData,
+				data: u,
+				token: guard.token,
+				vtable: guard.vtable,
+			},
+			Guard {
+				_marker: PhantomData,
+				data: v,
+				token,
+				vtable: guard.vtable,
+			},
+		)
+	}
+
+	/// Change the lifetime of `guard` to an arbitrary one.
+	///
+	/// This is an escape hatch for storing a guard next to the [`Rcu`] it
+	/// borrows from, as in self-referential structs. Prefer an
+	/// [`RcuHandle`] or a [`Snapshot`] where possible.
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::lifetime_extend(...)`. A method would interfere with methods
+	/// of the same name on the contents of the guard.
+	///
+	/// # Safety
+	///
+	/// The [`Rcu`] that `guard` was created from must not be dropped, nor
+	/// moved, for as long as the returned guard is alive. Keeping it in an
+	/// `Arc` that outlives the guard is enough.
+	///
+	/// [`RcuHandle`]: crate::RcuHandle
+	/// [`Snapshot`]: crate::Snapshot
+	#[must_use]
+	pub unsafe fn lifetime_extend<'b>(guard: Self) -> Guard<'b, T> {
+		let guard = ManuallyDrop::new(guard);
+
+		Guard {
+			_marker: PhantomData,
+			data: guard.data,
+			token: guard.token,
+			vtable: guard.vtable,
+		}
+	}
+
+	/// Make a new guard to the same value, that keeps it alive
+	/// independently of `guard`.
+	///
+	/// This is not [`Clone`], so that `guard.clone()` keeps cloning the
+	/// value.
+	pub(crate) fn duplicate(guard: &Self) -> Self {
+		Guard {
+			_marker: PhantomData,
+			data: guard.data,
+			token: unsafe { (guard.vtable.reprotect)(guard.token) },
+			vtable: guard.vtable,
+		}
+	}
+
+	/// Get a raw pointer to the guarded value.
+	///
+	/// The pointer is only valid for as long as the guard is live. It must
+	/// not be dereferenced after the guard is dropped.
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::as_ptr(...)`. A method would interfere with methods of the
+	/// same name on the contents of the guard.
+	#[must_use]
+	pub const fn as_ptr(guard: &Self) -> *const T {
+		guard.data
+	}
+
+	/// Check whether `a` and `b` reference the same version of the value.
+	///
+	/// This compares pointers, not values, so it takes the same time for
+	/// any `T`. Two versions that are equal by value still count as
+	/// different. Use it to find out whether a fresh [`get`] returned the
+	/// same version as a guard obtained earlier.
+	///
+	/// Guards that have been [mapped] to different components of the same
+	/// version count as different.
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::same_version(...)`. A method would interfere with methods
+	/// of the same name on the contents of the guard.
+	///
+	/// [`get`]: Rcu::get
+	/// [mapped]: Guard::map
+	#[must_use]
+	pub fn same_version(a: &Self, b: &Self) -> bool {
+		// Each guard keeps its version alive, so two different versions
+		// cannot share an address while both guards exist.
+		ptr::eq(a.data, b.data)
+	}
+
+	/// Clone the guarded value and drop the guard.
+	///
+	/// This is an associated function that needs to be used as
+	/// `Guard::into_owned(...)`. A method would interfere with methods of
+	/// the same name on the contents of the guard.
+	#[must_use]
+	pub fn into_owned(guard: Self) -> T
+	where
+		T: Clone,
+	{
+		let data = T::clone(&guard);
+		drop(guard);
+		data
+	}
+}
+
+impl<'a, T: ?Sized> Deref for Guard<'a, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe { &*self.data }
+	}
+}
+
+impl<'a, T: ?Sized> AsRef<T> for Guard<'a, T> {
+	fn as_ref(&self) -> &T {
+		self
+	}
+}
+
+// `Eq`, `Ord` and `Hash` of a guard all forward to the value, as `Borrow`
+// requires.
+impl<'a, T: ?Sized> Borrow<T> for Guard<'a, T> {
+	fn borrow(&self) -> &T {
+		self
+	}
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Guard<'_, T> {
+	fn eq(&self, other: &Self) -> bool {
+		**self == **other
+	}
+}
+
+impl<T: ?Sized + Eq> Eq for Guard<'_, T> {}
+
+/// Compare the values the guards reference.
+///
+/// That is the value each [`Rcu`] had when the guard was created, not the
+/// current one.
+impl<T: ?Sized + PartialOrd> PartialOrd for Guard<'_, T> {
+	fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+		(**self).partial_cmp(&**other)
+	}
+}
+
+impl<T: ?Sized + Ord> Ord for Guard<'_, T> {
+	fn cmp(&self, other: &Self) -> CmpOrdering {
+		(**self).cmp(&**other)
+	}
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<T> for Guard<'_, T> {
+	fn eq(&self, other: &T) -> bool {
+		**self == *other
+	}
+}
+
+impl<T: PartialEq, R: Reclaim> PartialEq<Rcu<T, R>> for Guard<'_, T> {
+	fn eq(&self, other: &Rcu<T, R>) -> bool {
+		**self == *other.get()
+	}
+}
+
+impl<T: ?Sized + Hash> Hash for Guard<'_, T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		(**self).hash(state);
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Guard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Guard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&**self, f)
+	}
+}
+
+/// Format the address of the guarded value.
+impl<T: ?Sized> fmt::Pointer for Guard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Pointer::fmt(&self.data, f)
+	}
+}
+
+impl<'a, T: ?Sized> Drop for Guard<'a, T> {
+	fn drop(&mut self) {
+		unsafe { (self.vtable.release)(self.token) };
+	}
+}
+
+// Dropping a guard might drop the value it references.
+unsafe impl<T: ?Sized + Sync> Sync for Guard<'_, T> {}
+unsafe impl<T: ?Sized + Send + Sync> Send for Guard<'_, T> {}
+
+/// The RAII guard returned by [`Rcu::replace`].
+///
+/// It keeps the value the [`Rcu`] had before it was replaced alive. The old
+/// value is freed once this guard and all other guards that reference it
+/// are dropped.
+pub struct OldGuard<'a, T> {
+	guard: Guard<'a, T>,
+	/// [`Reclaim::try_unwrap`] of the strategy that protects `guard`.
+	try_unwrap: unsafe fn(*const ()) -> Option<T>,
+}
+
+impl<'a, T: Clone> OldGuard<'a, T> {
+	/// Take the old value out of the guard.
+	///
+	/// If this is the last guard referencing the old value and the
+	/// reclamation strategy allows it, the value is moved out without
+	/// cloning. Otherwise, it is cloned.
+	///
+	/// This is an associated function that needs to be used as
+	/// `OldGuard::into_inner(...)`. A method would interfere with methods
+	/// of the same name on the contents of the guard.
+	#[must_use]
+	pub fn into_inner(guard: Self) -> T {
+		let Self { guard, try_unwrap } = guard;
+		let guard = ManuallyDrop::new(guard);
+
+		unsafe { try_unwrap(guard.token) }.unwrap_or_else(|| {
+			let data = T::clone(&guard);
+			drop(ManuallyDrop::into_inner(guard));
+			data
+		})
+	}
+}
+
+impl<'a, T> Deref for OldGuard<'a, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.guard
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for OldGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: fmt::Display> fmt::Display for OldGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&**self, f)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use portable_atomic::AtomicBool;
+
+	use std::panic::{catch_unwind, AssertUnwindSafe};
+	use std::sync::Arc;
+	use std::thread::{scope, sleep};
+	use std::time::Duration;
+
+	use crate::testing::stress;
+	use crate::RcuString;
+
+	type UserRcu = Rcu<User>;
+
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	struct User {
+		id: i32,
+		name: &'static str,
+	}
+
+	impl User {
+		const A: Self = Self { id: 1, name: "user 1" };
+
+		const B: Self = Self { id: 2, name: "user 2" };
+	}
+
+	#[test]
+	fn test_rcu() {
+		fn routine<'a>(
+			start_in: u64,
+			run_for: u64,
+			rcu: &'a UserRcu,
+			expected: User,
+		) -> impl FnOnce() + Send + 'a {
+			const CHECK_COUNT: u32 = 5;
+
+			move || {
+				sleep(Duration::from_secs(start_in));
+
+				let user = rcu.get();
+
+				let t = Duration::from_secs(run_for) / CHECK_COUNT;
+				for _ in 0..CHECK_COUNT {
+					sleep(t);
+					assert_eq!(*user, expected);
+				}
+			}
+		}
+
+		let user = Rcu::new(User::A);
+
+		scope(|scope| {
+			scope.spawn(routine(0, 10, &user, User::A));
+			scope.spawn(routine(4, 15, &user, User::A));
+
+			// Any readers past t=5 must see User::B
+			scope.spawn(routine(6, 4, &user, User::B));
 			scope.spawn(routine(8, 5, &user, User::B));
 			scope.spawn(routine(10, 7, &user, User::B));
 
-			sleep(Duration::from_secs(5));
+			sleep(Duration::from_secs(5));
+			user.update(User::B);
+		});
+	}
+
+	#[test]
+	fn test_get_update_race() {
+		// A freed `Inner` is very likely to be reused by the next `update`,
+		// so a guard that raced with a free would see a torn value.
+		let rcu = Rcu::new(vec![0; 16]);
+		stress(
+			&rcu,
+			100_000,
+			|i| vec![i; 16],
+			|v| {
+				assert!(v.iter().all(|x| *x == v[0]));
+			},
+		);
+	}
+
+	#[test]
+	fn test_guard_split() {
+		let user = Rcu::new(User::A);
+
+		let (id, name) =
+			Guard::split(user.get(), |x| (&x.id, &x.name));
+		user.update(User::B);
+
+		assert_eq!(user.reader_count(), 0);
+		drop(id);
+		assert_eq!(*name, User::A.name);
+	}
+
+	#[test]
+	fn test_guard_into_owned() {
+		let user = Rcu::new(User::A);
+
+		let owned = Guard::into_owned(user.get());
+		assert_eq!(user.reader_count(), 0);
+
+		user.update(User::B);
+		assert_eq!(owned, User::A);
+	}
+
+	#[test]
+	fn test_guard_as_ref() {
+		fn len(x: impl AsRef<str>) -> usize {
+			x.as_ref().len()
+		}
+
+		let rcu = Rcu::new(String::from("abc"));
+		assert_eq!(len(Guard::map(rcu.get(), String::as_str)), 3);
+	}
+
+	#[test]
+	fn test_guard_borrow() {
+		use std::collections::HashMap;
+
+		let a = Rcu::new(1);
+		let b = Rcu::new(2);
+
+		let names: HashMap<_, _> =
+			[(a.get(), "a"), (b.get(), "b")].into();
+		assert_eq!(names.get(&1), Some(&"a"));
+		assert_eq!(names.get(&2), Some(&"b"));
+		assert_eq!(names.get(&3), None);
+	}
+
+	#[test]
+	fn test_guard_ptr() {
+		let user = Rcu::new(User::A);
+
+		let a = user.get();
+		let ptr = Guard::as_ptr(&a);
+		user.update(User::B);
+
+		let b = unsafe { Guard::from_ptr(ptr, &user) };
+		drop(a);
+
+		assert_eq!(*b, User::A);
+		assert_eq!(Guard::as_ptr(&b), ptr);
+	}
+
+	#[test]
+	fn test_guard_same_version() {
+		let user = Rcu::new(User::A);
+
+		let a = user.get();
+		assert!(Guard::same_version(&a, &user.get()));
+
+		user.update(User::A);
+		let b = user.get();
+		assert_eq!(a, b);
+		assert!(!Guard::same_version(&a, &b));
+	}
+
+	#[test]
+	fn test_inner_layout() {
+		let inner = reclaim::alloc(0u8);
+
+		let offset = unsafe {
+			Inner::data(inner).addr() - inner.cast_const().addr()
+		};
+		assert_eq!(offset, 64);
+
+		unsafe { Inner::free(inner) };
+	}
+
+	#[test]
+	fn test_guard_map() {
+		let user = Rcu::new(User::A);
+
+		let name = Guard::map(user.get(), |x| &x.name);
+		user.update(User::B);
+
+		assert_eq!(*name, User::A.name);
+		assert_eq!(user.get().name, User::B.name);
+	}
+
+	#[test]
+	fn test_guard_map_unsized() {
+		use std::fmt::Display;
+
+		let rcu: Rcu<Box<dyn Display + Send + Sync>> =
+			Rcu::new(Box::new(42));
+		let display = Guard::map(rcu.get(), |x| &**x);
+		assert_eq!(display.to_string(), "42");
+
+		let rcu = Rcu::new(vec![1, 2, 3]);
+		let slice = Guard::map(rcu.get(), |x| &x[1..]);
+		assert_eq!(*slice, [2, 3]);
+
+		let rcu: Rcu<Box<[i32]>> = Rcu::new(Box::new([1, 2, 3]));
+		let old = Guard::map(rcu.get(), |x| &**x);
+		rcu.update(Box::new([4]));
+		assert_eq!(*old, [1, 2, 3]);
+		assert_eq!(*Guard::map(rcu.get(), |x| &**x), [4]);
+	}
+
+	#[test]
+	fn test_try_into_inner() {
+		let user = Rcu::new(User::A);
+		user.update(User::B);
+
+		assert_eq!(user.try_into_inner().ok(), Some(User::B));
+	}
+
+	#[test]
+	fn test_from_box() {
+		let rcu = Rcu::from_box(Box::new([7u8; 1024]));
+		assert!(rcu.get().iter().all(|x| *x == 7));
+
+		let rcu = Rcu::from_box(Box::new(String::from("a")));
+		assert_eq!(Rcu::take(rcu), "a");
+	}
+
+	#[test]
+	fn test_take() {
+		let user = Rcu::new(User::A);
+
+		scope(|scope| {
+			scope.spawn(|| {
+				let _guard = user.get();
+				user.update(User::B);
+			});
+		});
+
+		assert_eq!(Rcu::take(user), User::B);
+	}
+
+	#[test]
+	#[cfg_attr(miri, ignore = "Miri reports the leaked value")]
+	fn test_leak() {
+		let rcu = Rcu::new(String::from("a"));
+		rcu.update(String::from("b"));
+
+		let leaked: &'static String = Rcu::leak(rcu);
+		assert_eq!(leaked, "b");
+	}
+
+	#[test]
+	fn test_option() {
+		let user = Rcu::new(None);
+		assert!(user.take_option().is_none());
+
+		user.put_option(User::A);
+		let a = user.take_option().unwrap();
+		assert_eq!(*a, User::A);
+
+		user.clear_option();
+		assert!(user.take_option().is_none());
+		assert_eq!(*a, User::A);
+	}
+
+	#[test]
+	fn test_compare_exchange_ptr() {
+		let user = Rcu::new(User::A);
+		let (current, guard) = user.load_node();
+		let new = Inner::new(User::B);
+
+		let r = unsafe {
+			user.compare_exchange_ptr(
+				current,
+				new,
+				Ordering::SeqCst,
+				Ordering::Relaxed,
+			)
+		};
+		assert_eq!(r, Ok(current));
+
+		unsafe {
+			let token = user.reclaim().retire(current);
+			RefCounting::release::<User>(token);
+		}
+
+		assert_eq!(*guard, User::A);
+		assert_eq!(*user.get(), User::B);
+
+		let r = unsafe {
+			user.compare_exchange_ptr(
+				current,
+				new,
+				Ordering::SeqCst,
+				Ordering::Relaxed,
+			)
+		};
+		assert_eq!(r, Err(new));
+	}
+
+	#[test]
+	fn test_with_mut() {
+		let user = Rcu::new(User::A);
+		let old = user.get();
+
+		user.with_mut(|x| x.id = User::B.id);
+		assert_eq!(old.id, User::A.id);
+		assert_eq!(user.get().id, User::B.id);
+
+		let r = catch_unwind(AssertUnwindSafe(|| {
+			user.with_mut(|x| {
+				x.id = User::A.id;
+				panic!();
+			});
+		}));
+		assert!(r.is_err());
+		assert_eq!(user.get().id, User::B.id);
+		assert!(user.is_poisoned());
+	}
+
+	#[test]
+	fn test_poison() {
+		let rcu = Rcu::new(0);
+
+		rcu.update_with(|x| x + 1);
+		assert!(!rcu.is_poisoned());
+
+		let r = catch_unwind(AssertUnwindSafe(|| {
+			rcu.update_with(|_| panic!());
+		}));
+		assert!(r.is_err());
+		assert!(rcu.is_poisoned());
+		assert_eq!(*rcu.get(), 1);
+
+		rcu.clear_poison();
+		assert!(!rcu.is_poisoned());
+	}
+
+	#[test]
+	fn test_string() {
+		let rcu = RcuString::default();
+		let empty = rcu.get();
+
+		scope(|scope| {
+			for _ in 0..4 {
+				scope.spawn(|| rcu.push_str("ab"));
+			}
+		});
+
+		assert_eq!(*rcu.get(), "abababab");
+		assert_eq!(*empty, "");
+
+		rcu.clear();
+		assert_eq!(*rcu.get(), "");
+	}
+
+	#[test]
+	fn test_rcu_ord() {
+		let a = Rcu::new(1);
+		let b = Rcu::new(2);
+		assert!(a < b);
+		assert_eq!(a.cmp(&b), CmpOrdering::Less);
+
+		a.update(3);
+		assert!(a > b);
+	}
+
+	#[test]
+	fn test_get_if() {
+		let user = Rcu::new(User::A);
+
+		assert_eq!(
+			*user.get_if(|x| x.id == User::A.id).unwrap(),
+			User::A
+		);
+		assert!(user.get_if(|x| x.id == User::B.id).is_none());
+		assert_eq!(user.reader_count(), 0);
+	}
+
+	#[test]
+	fn test_lifetime_extend() {
+		struct Owner {
+			guard: Guard<'static, User>,
+			_rcu: Arc<UserRcu>,
+		}
+
+		let rcu = Arc::new(Rcu::new(User::A));
+		// SAFETY: `Owner` keeps a clone of `rcu` alive for as long as the
+		// guard, and declares it after the guard so it is dropped last.
+		let guard = unsafe { Guard::lifetime_extend(rcu.get()) };
+		let owner = Owner { guard, _rcu: Arc::clone(&rcu) };
+		drop(rcu);
+
+		assert_eq!(*owner.guard, User::A);
+	}
+
+	#[test]
+	fn test_try_get() {
+		let user = Rcu::new(User::A);
+		let guards: Vec<_> =
+			(0..3).map(|_| user.try_get().unwrap()).collect();
+
+		assert!(guards.iter().all(|x| **x == User::A));
+		assert_eq!(user.reader_count(), 3);
+	}
+
+	#[test]
+	#[cfg_attr(
+		miri,
+		ignore = "Miri reports the node kept alive by the forgotten guard"
+	)]
+	fn test_get_mut() {
+		let mut user = Rcu::new(User::A);
+
+		*user.get_mut().unwrap() = User::B;
+		assert_eq!(*user.get(), User::B);
+
+		mem::forget(user.get());
+		assert!(user.get_mut().is_none());
+	}
+
+	#[test]
+	fn test_reader_count() {
+		let user = Rcu::new(User::A);
+		assert_eq!(user.reader_count(), 0);
+
+		let a = user.get();
+		let b = user.get();
+		assert_eq!(user.reader_count(), 2);
+
+		user.update(User::B);
+		assert_eq!(user.reader_count(), 0);
+
+		drop((a, b));
+		assert_eq!(user.reader_count(), 0);
+	}
+
+	#[test]
+	fn test_synchronize() {
+		let user = Rcu::new(User::A);
+		user.synchronize();
+
+		let done = AtomicBool::new(false);
+		scope(|scope| {
+			let guard = user.get();
+			scope.spawn(|| {
+				user.synchronize();
+				assert!(done.load(Ordering::Relaxed));
+			});
+
+			sleep(Duration::from_millis(100));
+			done.store(true, Ordering::Relaxed);
+			drop(guard);
+		});
+
+		let _guard = user.get();
+		scope(|scope| {
+			scope.spawn(|| user.synchronize());
 			user.update(User::B);
 		});
 	}
+
+	#[test]
+	fn test_replace() {
+		let user = Rcu::new(User::A);
+
+		let reader = user.get();
+		let old = user.replace(User::B);
+		assert_eq!(*old, User::A);
+		assert_eq!(*user.get(), User::B);
+
+		drop(reader);
+		assert_eq!(OldGuard::into_inner(old), User::A);
+	}
+
+	#[test]
+	fn test_clone() {
+		let a = Rcu::new(User::A);
+		let b = a.clone();
+
+		a.update(User::B);
+		assert_eq!(*a.get(), User::B);
+		assert_eq!(*b.get(), User::A);
+	}
+
+	#[test]
+	fn test_with() {
+		let user = Rcu::new(User::A);
+
+		assert_eq!(user.with(|x| x.id), User::A.id);
+		assert_eq!(user.reader_count(), 0);
+	}
+
+	#[test]
+	fn test_default_from() {
+		let rcu: Rcu<i32> = Rcu::default();
+		assert_eq!(*rcu.get(), 0);
+
+		let rcu: Rcu<User> = User::A.into();
+		assert_eq!(*rcu.get(), User::A);
+	}
+
+	#[test]
+	fn test_eq() {
+		for v in [1, 2, 3, 42, i32::MAX] {
+			let rcu = Rcu::new(v);
+			assert_eq!(rcu, rcu);
+			assert_eq!(rcu, Rcu::new(v));
+			assert_ne!(rcu, Rcu::new(v.wrapping_add(1)));
+
+			let guard = rcu.get();
+			assert_eq!(guard, rcu.get());
+			assert_eq!(guard, v);
+			assert!(rcu == guard);
+			assert!(guard == rcu);
+
+			rcu.update(v.wrapping_add(1));
+			assert!(rcu != guard);
+			assert_ne!(guard, rcu.get());
+		}
+	}
+
+	#[test]
+	fn test_ord() {
+		use std::collections::BTreeSet;
+
+		let rcus = [Rcu::new(3), Rcu::new(1), Rcu::new(2)];
+
+		let mut guards: Vec<_> = rcus.iter().map(Rcu::get).collect();
+		guards.sort();
+		assert!(guards.iter().map(|x| **x).eq([1, 2, 3]));
+
+		rcus[0].update(0);
+		let set: BTreeSet<_> = rcus.iter().map(Rcu::get).collect();
+		assert_eq!(set.first(), Some(&rcus[0].get()));
+		assert!(guards[0] < guards[1]);
+	}
+
+	#[test]
+	fn test_hash() {
+		use std::collections::HashSet;
+
+		let a = Rcu::new(1);
+		let b = Rcu::new(1);
+		let c = Rcu::new(2);
+
+		let guards: HashSet<_> = [a.get(), b.get(), c.get()].into();
+		assert_eq!(guards.len(), 2);
+		assert!(guards.contains(&a.get()));
+		assert!(guards.contains(&c.get()));
+	}
+
+	#[test]
+	fn test_fmt() {
+		let rcu = Rcu::new(42);
+		assert_eq!(format!("{rcu:?}"), "Rcu { value: 42 }");
+		assert_eq!(format!("{rcu}"), "42");
+
+		let guard = rcu.get();
+		assert_eq!(format!("{guard:?}"), "42");
+		assert_eq!(format!("{guard:>4}"), "  42");
+	}
+
+	#[test]
+	fn test_fmt_pointer() {
+		let user = Rcu::new(User::A);
+
+		let a = user.get();
+		let b = user.get();
+		assert_eq!(format!("{a:p}"), format!("{b:p}"));
+		assert_eq!(format!("{user:p}"), format!("{a:p}"));
+
+		user.update(User::B);
+		let c = user.get();
+		assert_ne!(format!("{a:p}"), format!("{c:p}"));
+		assert_eq!(format!("{user:p}"), format!("{c:p}"));
+	}
+
+	#[test]
+	fn test_get_version() {
+		let user = Rcu::new(User::A);
+
+		let (a, v1) = user.get_version();
+		user.update(User::B);
+		let (b, v2) = user.get_version();
+
+		assert_eq!((&*a, v1), (&User::A, 0));
+		assert_eq!((&*b, v2), (&User::B, 1));
+	}
+
+	#[test]
+	fn test_idempotent_update() {
+		let user = Rcu::new(User::A);
+
+		assert!(!user.idempotent_update(User::A));
+		assert_eq!(user.get_version().1, 0);
+
+		assert!(user.idempotent_update(User::B));
+		assert_eq!(user.get_version().1, 1);
+		assert_eq!(*user.get(), User::B);
+	}
+
+	#[test]
+	fn test_reset_to_default() {
+		let rcu = Rcu::new(1);
+
+		let old = rcu.get();
+		rcu.reset_to_default();
+		assert_eq!(*old, 1);
+		assert_eq!(*rcu.get(), 0);
+	}
+
+	#[test]
+	fn test_update_if() {
+		let user = Rcu::new(User::A);
+
+		assert!(!user.update_if(User::B, |x| x.id > 1));
+		assert_eq!(*user.get(), User::A);
+
+		assert!(user.update_if(User::B, |x| x.id == 1));
+		assert_eq!(*user.get(), User::B);
+	}
+
+	#[test]
+	fn test_try_update() {
+		let user = Rcu::new(User::A);
+
+		assert!(user.try_update(User::B));
+		assert_eq!(*user.get(), User::B);
+	}
+
+	#[test]
+	fn test_observer() {
+		use std::sync::{Arc, Mutex};
+
+		let log = Arc::new(Mutex::new(Vec::new()));
+		let user = {
+			let log = Arc::clone(&log);
+			Rcu::with_observer(
+				User::A,
+				move |old: &User, new: &User| {
+					log.lock().unwrap().push((old.id, new.id));
+				},
+			)
+		};
+
+		user.update(User::B);
+		assert!(user.update_with(|_| User::A));
+		assert!(user.compare_and_update(&User::B, User::A).is_err());
+
+		assert_eq!(*log.lock().unwrap(), [(1, 2), (2, 1)]);
+	}
+
+	#[test]
+	fn test_wait_changed() {
+		let user = Rcu::new(User::A);
+
+		let a = user.get();
+		scope(|scope| {
+			scope.spawn(|| {
+				sleep(Duration::from_millis(100));
+				user.update(User::B);
+			});
+
+			assert_eq!(*user.wait_changed(&a), User::B);
+		});
+
+		// Already changed.
+		assert_eq!(*user.wait_changed(&a), User::B);
+	}
+
+	#[test]
+	fn test_generation() {
+		let user = Rcu::new(User::A);
+		assert_eq!(user.generation(), 0);
+
+		user.update(User::B);
+		assert!(!user.idempotent_update(User::B));
+		assert!(user.update_with(|_| User::A));
+		assert_eq!(user.generation(), 2);
+		assert_eq!(user.get_version().1, user.generation());
+	}
+
+	#[test]
+	fn test_update_with() {
+		let rcu = Rcu::new(0);
+
+		assert!(rcu.update_with(|x| x + 1));
+		assert_eq!(*rcu.get(), 1);
+
+		assert!(!rcu.update_with(|x| *x));
+		assert_eq!(*rcu.get(), 1);
+	}
+
+	#[test]
+	fn test_try_update_with() {
+		let rcu = Rcu::new(0);
+
+		assert_eq!(rcu.try_update_with(0, |x| x + 1), Ok(()));
+		assert_eq!(*rcu.get(), 1);
+
+		// Every attempt loses against the update made inside `f`.
+		let r = rcu.try_update_with(2, |x| {
+			rcu.update(x + 10);
+			x + 1
+		});
+		assert_eq!(
+			r,
+			Err(TryUpdateError::TooManyRetries { attempts: 3 })
+		);
+		assert_eq!(*rcu.get(), 31);
+	}
+
+	#[test]
+	fn test_compare_and_update() {
+		let user = Rcu::new(User::A);
+
+		assert_eq!(
+			user.compare_and_update(&User::B, User::B),
+			Err(User::B)
+		);
+		assert_eq!(*user.get(), User::A);
+
+		assert_eq!(
+			user.compare_and_update(&User::A, User::B),
+			Ok(())
+		);
+		assert_eq!(*user.get(), User::B);
+	}
+
+	#[test]
+	fn test_update_with_contended() {
+		const THREADS: usize = 8;
+		const INCREMENTS: usize = 1000;
+
+		let rcu = Rcu::new(0);
+
+		scope(|scope| {
+			for _ in 0..THREADS {
+				scope.spawn(|| {
+					for _ in 0..INCREMENTS {
+						rcu.update_with(|x| x + 1);
+					}
+				});
+			}
+		});
+
+		assert_eq!(*rcu.get(), THREADS * INCREMENTS);
+	}
 }
@@ -0,0 +1,149 @@
+use core::fmt;
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::{Inner, Reclaim, RefCounting};
+
+/// A reader that keeps its guard between reads.
+///
+/// Every [`Rcu::get`] protects the current value, which for the default
+/// strategy means an atomic read-modify-write on the ref count shared by
+/// all readers. A [`ThreadReader`] instead holds on to a guard to the
+/// value it read last, and only gets a new one once the value has been
+/// replaced. As long as the value does not change, a read is a single
+/// atomic load.
+///
+/// The trade-off is that the reader keeps the value it read last alive
+/// until its next read after an update, or until it is dropped. Create
+/// one per thread and drop it when the thread is done reading.
+///
+/// See: [`Rcu::register_reader`].
+pub struct ThreadReader<'a, T, R: Reclaim = RefCounting> {
+	rcu: &'a Rcu<T, R>,
+	node: *mut Inner<T>,
+	/// Only `None` if taking a new guard panicked.
+	guard: Option<Guard<'a, T>>,
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Create a new [`ThreadReader`] for this [`Rcu`].
+	pub fn register_reader(&self) -> ThreadReader<'_, T, R> {
+		let (node, guard) = self.load_node();
+		ThreadReader { rcu: self, node, guard: Some(guard) }
+	}
+}
+
+impl<'a, T, R: Reclaim> ThreadReader<'a, T, R> {
+	/// Get the current value.
+	///
+	/// This function does _not_ block execution.
+	pub fn get(&mut self) -> &T {
+		self.guard()
+	}
+
+	/// Get a guard to the current value that outlives the reader.
+	///
+	/// See: [`Rcu::get`].
+	pub fn get_guard(&mut self) -> Guard<'a, T> {
+		Guard::duplicate(self.guard())
+	}
+
+	fn guard(&mut self) -> &Guard<'a, T> {
+		// `guard` keeps `node` alive, so its address cannot be reused.
+		if !self.rcu.is_current(self.node) {
+			// Drop the old guard before taking a new one. `Epoch` only pins
+			// a thread at a newer epoch once all of its guards are gone.
+			self.guard = None;
+		}
+
+		self.guard.get_or_insert_with(|| {
+			let (node, guard) = self.rcu.load_node();
+			self.node = node;
+			guard
+		})
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug
+	for ThreadReader<'_, T, R>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut f = f.debug_tuple("ThreadReader");
+		if let Some(x) = &self.guard {
+			f.field(&**x);
+		}
+		f.finish()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::thread::{scope, sleep};
+	use std::time::Duration;
+
+	use portable_atomic::{AtomicUsize, Ordering};
+
+	use crate::reclaim::Epoch;
+	use crate::testing::Counted;
+	use crate::EpochRcu;
+
+	#[test]
+	fn test_reader() {
+		let rcu = Rcu::new(1);
+		let mut reader = rcu.register_reader();
+
+		assert_eq!(*reader.get(), 1);
+		assert_eq!(rcu.reader_count(), 1);
+
+		rcu.update(2);
+		assert_eq!(*reader.get(), 2);
+		assert_eq!(rcu.reader_count(), 1);
+
+		let guard = reader.get_guard();
+		assert_eq!(*guard, 2);
+		assert_eq!(rcu.reader_count(), 2);
+		drop(guard);
+
+		drop(reader);
+		assert_eq!(rcu.reader_count(), 0);
+	}
+
+	#[test]
+	fn test_reader_threads() {
+		let rcu = Rcu::new(0);
+
+		scope(|scope| {
+			scope.spawn(|| {
+				let mut reader = rcu.register_reader();
+				while *reader.get() != 100 {}
+			});
+
+			for i in 1..=100 {
+				rcu.update(i);
+			}
+		});
+	}
+
+	#[test]
+	fn test_reader_epoch() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			EpochRcu::with_reclaim(Counted(&drops), Epoch::new());
+		let mut reader = rcu.register_reader();
+
+		// A reader that stayed pinned at the epoch of its first read would
+		// keep the epoch from ever advancing far enough to free anything.
+		for _ in 0..1000 {
+			reader.get();
+			if drops.load(Ordering::Relaxed) >= 10 {
+				return;
+			}
+
+			rcu.update(Counted(&drops));
+			sleep(Duration::from_millis(1));
+		}
+
+		panic!("retired values were never freed");
+	}
+}
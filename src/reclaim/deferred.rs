@@ -0,0 +1,360 @@
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
+
+use portable_atomic::{fence, AtomicPtr, AtomicU8, Ordering};
+
+use super::{Inner, Reclaim, RefCounting, Retired};
+use crate::refs::Refs;
+
+/// The slot is empty.
+const EMPTY: u8 = 0;
+/// The slot is being written to or drained.
+const BUSY: u8 = 1;
+/// The slot holds a retired node.
+const FULL: u8 = 2;
+/// The slot is kept empty for an update that is about to retire a node.
+const RESERVED: u8 = 3;
+
+struct Slot {
+	state: AtomicU8,
+	retired: UnsafeCell<MaybeUninit<Retired>>,
+}
+
+// `retired` is only accessed while the slot is locked.
+unsafe impl Sync for Slot {}
+
+impl Slot {
+	const fn new() -> Self {
+		Self {
+			state: AtomicU8::new(EMPTY),
+			retired: UnsafeCell::new(MaybeUninit::uninit()),
+		}
+	}
+
+	/// Lock the slot if it is in `state`.
+	fn lock(&self, state: u8) -> bool {
+		self.swap_state(state, BUSY)
+	}
+
+	/// Move the slot from `from` to `to`, if it is in `from`.
+	fn swap_state(&self, from: u8, to: u8) -> bool {
+		self.state
+			.compare_exchange(
+				from,
+				to,
+				Ordering::Acquire,
+				Ordering::Relaxed,
+			)
+			.is_ok()
+	}
+
+	fn unlock(&self, state: u8) {
+		self.state.store(state, Ordering::Release);
+	}
+}
+
+/// Reclaim values by counting references, but only free them when asked
+/// to.
+///
+/// This is the same as [`RefCounting`], except that dropping a guard
+/// never frees a value. Instead, replaced values are kept in a queue of
+/// `N` slots stored inline, until [`drain_retired`] frees those that are
+/// not in use anymore. This moves the cost of dropping old values out of
+/// readers and into a place of your choosing, like a background thread.
+///
+/// Updates never free values or wait for a slot to free up. Before
+/// swapping in a new value, an update reserves a slot for the old one. If
+/// the queue is full, [`Rcu::try_replace`] hands the new value back, and
+/// all other updates panic. Choose `N` large enough to hold all values
+/// replaced between two drains. `N` must not be zero:
+///
+/// ```compile_fail
+/// use rcurs::reclaim::Deferred;
+///
+/// let _ = Deferred::<0>::new();
+/// ```
+///
+/// Updates still allocate the new value, and wait for readers that are
+/// taking a ref like with [`RefCounting`]. Both matter when calling them
+/// from an interrupt handler.
+///
+/// When the [`Rcu`] is dropped with a full queue, its last value is
+/// freed as soon as its last guard is gone instead.
+///
+/// [`drain_retired`]: Self::drain_retired
+/// [`Rcu`]: crate::Rcu
+/// [`Rcu::try_replace`]: crate::Rcu::try_replace
+pub struct Deferred<const N: usize> {
+	refs: RefCounting,
+	queue: [Slot; N],
+}
+
+impl<const N: usize> Deferred<N> {
+	/// Create a new [`Deferred`] with an empty queue.
+	#[must_use]
+	pub fn new() -> Self {
+		const { assert!(N > 0, "a Deferred needs at least one slot") };
+
+		Self {
+			refs: RefCounting::default(),
+			queue: [const { Slot::new() }; N],
+		}
+	}
+
+	/// Free the retired values that are not in use anymore.
+	///
+	/// Returns the number of values that were freed.
+	pub fn drain_retired(&self) -> usize {
+		let mut freed = 0;
+
+		for slot in &self.queue {
+			if !slot.lock(FULL) {
+				continue;
+			}
+
+			let retired =
+				unsafe { (*slot.retired.get()).assume_init_ref() };
+
+			// The queue holds a ref of its own, so the count can only drop to
+			// one once all guards are gone. No new guards can appear.
+			if retired.refs().count() != 1 {
+				slot.unlock(FULL);
+				continue;
+			}
+
+			// Synchronize with the release of the other refs so all accesses
+			// made through them happen before the value is freed.
+			fence(Ordering::Acquire);
+
+			let retired =
+				unsafe { (*slot.retired.get()).assume_init_read() };
+			unsafe { retired.free() };
+
+			slot.unlock(EMPTY);
+			freed += 1;
+		}
+
+		freed
+	}
+
+	/// Put `retired` in a reserved slot, or in an empty one if there is
+	/// none.
+	fn push(&self, retired: Retired) -> Result<(), Retired> {
+		// Reservations are not tied to an update, any reserved slot will
+		// do. There is one for every update that has reserved a slot but
+		// not pushed to it yet.
+		let Some(slot) =
+			self.queue.iter().find(|x| x.lock(RESERVED)).or_else(
+				|| self.queue.iter().find(|x| x.lock(EMPTY)),
+			)
+		else {
+			return Err(retired);
+		};
+
+		unsafe { (*slot.retired.get()).write(retired) };
+		slot.unlock(FULL);
+		Ok(())
+	}
+}
+
+impl<const N: usize> Default for Deferred<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+unsafe impl<const N: usize> Reclaim for Deferred<N> {
+	fn protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> (*mut Inner<T>, *const ()) {
+		self.refs.protect(ptr)
+	}
+
+	unsafe fn release<T>(token: *const ()) {
+		// The queue holds a ref until the node is freed by
+		// `drain_retired`, so this only frees nodes that did not fit in
+		// the queue.
+		RefCounting::release::<T>(token);
+	}
+
+	unsafe fn reprotect<T>(token: *const ()) -> *const () {
+		RefCounting::reprotect::<T>(token)
+	}
+
+	fn reserve(&self) -> bool {
+		self.queue.iter().any(|x| x.swap_state(EMPTY, RESERVED))
+	}
+
+	fn unreserve(&self) {
+		// There is a reserved slot for every reservation, see `push`.
+		let slot = self.queue.iter().find(|x| x.lock(RESERVED));
+		debug_assert!(
+			slot.is_some(),
+			"no reserved slot to give back"
+		);
+
+		if let Some(slot) = slot {
+			slot.unlock(EMPTY);
+		}
+	}
+
+	unsafe fn retire<T>(&self, node: *mut Inner<T>) -> *const () {
+		// The ref of the `Rcu` is handed over to the returned token, like
+		// `RefCounting` does. The queue takes one of its own.
+		let token = self.refs.retire(node);
+		(*node).refs.take_ref();
+
+		// Without a slot, the node is freed by the last release, like with
+		// `RefCounting`. Updates of the `Rcu` always reserve one, so this
+		// only happens when it is dropped. The token still holds a ref, so
+		// this one is not the last.
+		if self.push(Retired::new(node)).is_err() {
+			Refs::release_ref(&raw const (*node).refs);
+		}
+
+		token
+	}
+}
+
+impl<const N: usize> fmt::Debug for Deferred<N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let retired = self
+			.queue
+			.iter()
+			.filter(|x| x.state.load(Ordering::Relaxed) == FULL)
+			.count();
+
+		f.debug_struct("Deferred")
+			.field("retired", &retired)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<const N: usize> Drop for Deferred<N> {
+	fn drop(&mut self) {
+		// No guard can be alive anymore, see the safety section of
+		// `Reclaim`.
+		for slot in &mut self.queue {
+			if *slot.state.get_mut() == FULL {
+				let retired = unsafe {
+					slot.retired.get_mut().assume_init_read()
+				};
+				unsafe { retired.free() };
+			}
+		}
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::panic::{catch_unwind, AssertUnwindSafe};
+
+	use portable_atomic::AtomicUsize;
+
+	use crate::testing::{stress, Counted, Shared};
+	use crate::Rcu;
+
+	#[test]
+	fn test_deferred() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			Rcu::with_reclaim(Counted(&drops), Deferred::<4>::new());
+
+		let guard = rcu.get();
+		rcu.update(Counted(&drops));
+		rcu.update(Counted(&drops));
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+		assert_eq!(rcu.reclaim().drain_retired(), 1);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+		drop(guard);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+		assert_eq!(rcu.reclaim().drain_retired(), 1);
+		assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+		drop(rcu);
+		assert_eq!(drops.load(Ordering::Relaxed), 3);
+	}
+
+	#[test]
+	fn test_deferred_full() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			Rcu::with_reclaim(Counted(&drops), Deferred::<2>::new());
+
+		rcu.update(Counted(&drops));
+		rcu.update(Counted(&drops));
+
+		// The update neither frees nor waits, it hands the value back.
+		let Err(new) = rcu.try_replace(Counted(&drops)) else {
+			panic!("replaced a value with a full queue");
+		};
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+		drop(new);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+		let r = catch_unwind(AssertUnwindSafe(|| {
+			rcu.update(Counted(&drops));
+		}));
+		assert!(r.is_err());
+		assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+		// A full queue does not keep the `Rcu` from being dropped.
+		drop(rcu);
+		assert_eq!(drops.load(Ordering::Relaxed), 5);
+	}
+
+	#[test]
+	fn test_deferred_drain() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			Rcu::with_reclaim(Counted(&drops), Deferred::<2>::new());
+
+		let guard = rcu.get();
+		rcu.update(Counted(&drops));
+		rcu.update(Counted(&drops));
+		assert!(rcu.try_replace(Counted(&drops)).is_err());
+
+		assert_eq!(rcu.reclaim().drain_retired(), 1);
+		assert!(rcu.try_replace(Counted(&drops)).is_ok());
+		assert!(rcu.try_replace(Counted(&drops)).is_err());
+
+		drop(guard);
+		assert_eq!(rcu.reclaim().drain_retired(), 2);
+		assert_eq!(drops.load(Ordering::Relaxed), 5);
+	}
+
+	/// Updates `rcu` without ever panicking, by draining it whenever the
+	/// queue is full.
+	struct Draining<'a, T>(&'a Rcu<T, Deferred<4>>);
+
+	impl<T: Send + Sync> Shared<T> for Draining<'_, T> {
+		fn read(&self, f: impl FnOnce(&T)) {
+			self.0.read(f);
+		}
+
+		fn write(&self, mut new: T) {
+			while let Err(x) = self.0.try_replace(new) {
+				self.0.reclaim().drain_retired();
+				new = x;
+			}
+		}
+	}
+
+	#[test]
+	fn test_deferred_concurrent() {
+		let rcu =
+			Rcu::with_reclaim(vec![0; 16], Deferred::<4>::new());
+		stress(
+			&Draining(&rcu),
+			10_000,
+			|i| vec![i; 16],
+			|v| {
+				assert!(v.iter().all(|x| *x == v[0]));
+			},
+		);
+	}
+}
@@ -0,0 +1,371 @@
+//! Epoch-based reclamation.
+//!
+//! Instead of counting the references to each value, readers announce
+//! ("pin") the global epoch they observed when they started reading. A
+//! writer that swaps out a value tags it with the current global epoch and
+//! defers freeing it. The global epoch can only advance once all pinned
+//! readers have observed the current one. So after it has advanced twice
+//! past the tag of a retired value, no reader can still be using it.
+//!
+//! This is the same scheme `crossbeam-epoch` uses.
+
+use core::{fmt, ptr};
+
+use alloc::vec::Vec;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use portable_atomic::{fence, AtomicPtr, AtomicUsize, Ordering};
+
+use super::registry::{Entry, Registry};
+use super::{Inner, Reclaim, Retired};
+
+/// The global epoch.
+static EPOCH: AtomicUsize = AtomicUsize::new(0);
+
+/// All participants.
+///
+/// Participants are never freed. They are reused after the thread that
+/// owned them exits.
+static PARTICIPANTS: Registry<Participant> = Registry::new();
+
+/// The reader state of a thread.
+struct Participant {
+	/// The number of live guards pinned through this participant.
+	pins: AtomicUsize,
+	/// The epoch this participant was pinned at. Only meaningful while
+	/// `pins` is not zero.
+	epoch: AtomicUsize,
+}
+
+/// The participant owned by the current thread.
+struct Local(&'static Entry<Participant>);
+
+impl Drop for Local {
+	fn drop(&mut self) {
+		// Guards that were sent to other threads might still be pinned
+		// through this participant. That is fine, whoever takes it over
+		// next will simply stay pinned at their epoch until they are gone.
+		self.0.release();
+	}
+}
+
+std::thread_local! {
+	static LOCAL: Local = Local(PARTICIPANTS.acquire(|| Participant {
+		pins: AtomicUsize::new(0),
+		epoch: AtomicUsize::new(0),
+	}));
+}
+
+/// Pin the current thread to the global epoch.
+///
+/// Returns the participant that must be passed to [`unpin`] once the
+/// reader is done.
+fn pin() -> &'static Participant {
+	let p: &'static Participant = LOCAL.with(|local| local.0);
+
+	// Nested pins keep the epoch of the outermost one, which is always
+	// the older one.
+	if p.pins.fetch_add(1, Ordering::SeqCst) == 0 {
+		p.epoch
+			.store(EPOCH.load(Ordering::Relaxed), Ordering::Relaxed);
+		fence(Ordering::SeqCst);
+	}
+
+	p
+}
+
+/// Undo one [`pin`] of `participant`.
+///
+/// This does not need to run on the thread that pinned `participant`.
+unsafe fn unpin(participant: *const ()) {
+	let p = &*participant.cast::<Participant>();
+	p.pins.fetch_sub(1, Ordering::Release);
+}
+
+/// Try to advance the global epoch and return its current value.
+///
+/// The epoch cannot advance while any participant is pinned at an older
+/// epoch.
+fn try_advance() -> usize {
+	let epoch = EPOCH.load(Ordering::Relaxed);
+	fence(Ordering::SeqCst);
+
+	// If a participant is in the middle of pinning, we might see a stale
+	// `epoch` for it. A stale epoch is always older than the one it is
+	// about to store, so we can only ever be too conservative.
+	for p in PARTICIPANTS.iter() {
+		if p.pins.load(Ordering::Relaxed) != 0
+			&& p.epoch.load(Ordering::Relaxed) != epoch
+		{
+			return epoch;
+		}
+	}
+
+	// Synchronize with the unpins we observed so everything their guards
+	// read happens before we free anything.
+	fence(Ordering::Acquire);
+
+	let next = epoch.wrapping_add(1);
+	match EPOCH.compare_exchange(
+		epoch,
+		next,
+		Ordering::Release,
+		Ordering::Relaxed,
+	) {
+		Ok(_) => next,
+		Err(x) => x,
+	}
+}
+
+/// Tell the reclamation machinery that the calling thread is in a
+/// quiescent state.
+///
+/// This is meant for integration with frameworks that track quiescent
+/// states themselves. The calling thread must not hold any guard of an
+/// [`Rcu`] that uses [`Epoch`].
+///
+/// Threads that do not hold guards never hold up reclamation in this
+/// crate, so calling this is never required. It only tries to advance the
+/// global epoch, so that values retired before are freed sooner by
+/// [`Epoch::collect`] or the next update. Other strategies free values as
+/// soon as they can and are not affected at all.
+///
+/// [`Rcu`]: crate::Rcu
+pub fn quiescent_state() {
+	try_advance();
+}
+
+/// Reclaim values with epoch-based reclamation.
+///
+/// Instead of counting references to each value, readers only mark their
+/// own per-thread state. Readers on different threads thus never write to
+/// the same memory, so [`Rcu::get`] does not contend with other readers.
+///
+/// The trade-off is that swapped out values are not freed as soon as
+/// their last guard is dropped. They are freed by a later update after all
+/// readers have moved on. Until then, they stay in memory. Updates of the
+/// same [`Rcu`] also serialize on the list of these values.
+///
+/// [`Rcu`]: crate::Rcu
+/// [`Rcu::get`]: crate::Rcu::get
+#[derive(Default)]
+pub struct Epoch {
+	/// Nodes that have been retired but might still be in use.
+	garbage: Mutex<Vec<(Retired, usize)>>,
+}
+
+impl Epoch {
+	/// Create a new [`Epoch`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { garbage: Mutex::new(Vec::new()) }
+	}
+
+	/// Free the retired values that are not in use anymore.
+	///
+	/// Updates already do this, so calling it is only needed to free
+	/// values sooner if the [`Rcu`] is not updated for a while.
+	///
+	/// Returns the number of values that were freed.
+	///
+	/// [`Rcu`]: crate::Rcu
+	pub fn collect(&self) -> usize {
+		collect(&mut self.garbage())
+	}
+
+	fn garbage(&self) -> MutexGuard<'_, Vec<(Retired, usize)>> {
+		self.garbage.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+unsafe impl Reclaim for Epoch {
+	fn protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> (*mut Inner<T>, *const ()) {
+		let participant = pin();
+		let inner = ptr.load(Ordering::Acquire);
+		(inner, ptr::from_ref(participant).cast())
+	}
+
+	unsafe fn release<T>(token: *const ()) {
+		unpin(token);
+	}
+
+	unsafe fn reprotect<T>(token: *const ()) -> *const () {
+		// The participant is already pinned, so its epoch stays the same.
+		let p = &*token.cast::<Participant>();
+		p.pins.fetch_add(1, Ordering::Relaxed);
+		token
+	}
+
+	unsafe fn retire<T>(&self, node: *mut Inner<T>) -> *const () {
+		// Pinning before tagging `node` guarantees that its tag is not older
+		// than our pin, so it cannot be freed before we unpin.
+		let participant = pin();
+
+		fence(Ordering::SeqCst);
+		let epoch = EPOCH.load(Ordering::Relaxed);
+
+		let mut garbage = self.garbage();
+		garbage.push((Retired::new(node), epoch));
+
+		collect(&mut garbage);
+		ptr::from_ref(participant).cast()
+	}
+}
+
+impl fmt::Debug for Epoch {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Epoch")
+			.field("retired", &self.garbage().len())
+			.finish()
+	}
+}
+
+impl Drop for Epoch {
+	fn drop(&mut self) {
+		// No guard can be alive anymore, see the safety section of
+		// `Reclaim`.
+		let garbage = self
+			.garbage
+			.get_mut()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		for (x, _) in garbage.drain(..) {
+			unsafe { x.free() };
+		}
+	}
+}
+
+/// Try to advance the epoch and free the nodes in `garbage` that are at
+/// least two epochs old.
+///
+/// Each node is tagged with the global epoch at the time it was retired.
+fn collect(garbage: &mut Vec<(Retired, usize)>) -> usize {
+	let epoch = try_advance();
+
+	let mut freed = 0;
+	for (x, _) in
+		garbage.extract_if(.., |x| epoch.wrapping_sub(x.1) >= 2)
+	{
+		unsafe { x.free() };
+		freed += 1;
+	}
+
+	freed
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::thread::{scope, sleep};
+	use std::time::Duration;
+
+	use crate::testing::{stress, Counted};
+	use crate::EpochRcu;
+
+	/// Keep updating `rcu` until `done` returns `true`.
+	///
+	/// Readers of other tests running in parallel pin the same global
+	/// epoch, so reclamation can be delayed for a while.
+	fn update_until<T>(
+		rcu: &EpochRcu<T>,
+		mut new: impl FnMut() -> T,
+		mut done: impl FnMut() -> bool,
+	) {
+		for _ in 0..1000 {
+			if done() {
+				return;
+			}
+
+			rcu.update(new());
+			sleep(Duration::from_millis(1));
+		}
+
+		panic!("retired values were never freed");
+	}
+
+	#[test]
+	fn test_epoch() {
+		let rcu = EpochRcu::with_reclaim(1, Epoch::new());
+
+		let old = rcu.get();
+		rcu.update(2);
+
+		assert_eq!(*old, 1);
+		assert_eq!(*rcu.get(), 2);
+	}
+
+	#[test]
+	fn test_epoch_frees() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			EpochRcu::with_reclaim(Counted(&drops), Epoch::new());
+
+		update_until(
+			&rcu,
+			|| Counted(&drops),
+			|| drops.load(Ordering::Relaxed) >= 10,
+		);
+
+		let updates = rcu.get_version().1;
+		drop(rcu);
+		assert_eq!(drops.load(Ordering::Relaxed) as u64, updates + 1);
+	}
+
+	#[test]
+	fn test_epoch_collect() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			EpochRcu::with_reclaim(Counted(&drops), Epoch::new());
+		rcu.update(Counted(&drops));
+
+		for _ in 0..1000 {
+			quiescent_state();
+			if rcu.reclaim().collect() == 1 {
+				assert_eq!(drops.load(Ordering::Relaxed), 1);
+				return;
+			}
+
+			sleep(Duration::from_millis(1));
+		}
+
+		panic!("retired values were never freed");
+	}
+
+	#[test]
+	fn test_epoch_guard_on_other_thread() {
+		let drops = AtomicUsize::new(0);
+		let rcu =
+			EpochRcu::with_reclaim(Counted(&drops), Epoch::new());
+
+		let guard = rcu.get();
+		scope(|scope| {
+			scope.spawn(move || drop(guard));
+		});
+
+		// Had the guard not unpinned the participant of this thread, the
+		// epoch could never advance.
+		update_until(
+			&rcu,
+			|| Counted(&drops),
+			|| drops.load(Ordering::Relaxed) >= 1,
+		);
+	}
+
+	#[test]
+	fn test_epoch_concurrent() {
+		let rcu = EpochRcu::with_reclaim(vec![0; 16], Epoch::new());
+		stress(
+			&rcu,
+			10_000,
+			|i| vec![i; 16],
+			|v| {
+				assert!(v.iter().all(|x| *x == v[0]));
+			},
+		);
+	}
+}
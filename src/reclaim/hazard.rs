@@ -0,0 +1,229 @@
+//! Hazard pointers.
+//!
+//! A reader publishes ("hazards") the pointer it is about to use in a slot
+//! that writers can see. A writer that swaps out a value defers freeing
+//! it and, whenever it retires a value, frees every deferred value that is
+//! not in any slot.
+//!
+//! Why this is sound: a reader stores the pointer in its slot and then
+//! loads the pointer of the `Rcu` again. It only uses the value if the
+//! pointer has not changed in between. A writer swaps the pointer and then
+//! reads all slots. All four accesses are `SeqCst`, so they are ordered
+//! in a single total order. If the second load of the reader comes before
+//! the swap, then so does its store, and the writer sees the hazard. If it
+//! comes after the swap, the reader sees the new pointer and retries. So a
+//! reader never uses a value that the writer did not see as hazarded.
+
+use core::{fmt, ptr};
+
+use alloc::vec::Vec;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use portable_atomic::{fence, AtomicPtr, Ordering};
+
+use super::registry::{Entry, Registry};
+use super::{Inner, Reclaim, Retired};
+
+/// All slots.
+///
+/// Slots are never freed. They are reused after the guard that owned them
+/// is dropped.
+static SLOTS: Registry<Slot> = Registry::new();
+
+/// A hazard pointer.
+struct Slot {
+	/// The node protected by this slot, or null.
+	hazard: AtomicPtr<()>,
+}
+
+/// Take ownership of an unused slot or register a new one.
+fn acquire() -> &'static Entry<Slot> {
+	SLOTS.acquire(|| Slot { hazard: AtomicPtr::new(ptr::null_mut()) })
+}
+
+/// Clear the hazard and give up ownership of the slot.
+fn release(slot: &Entry<Slot>) {
+	slot.hazard.store(ptr::null_mut(), Ordering::Release);
+	slot.release();
+}
+
+/// Reclaim values with hazard pointers.
+///
+/// Readers do not write to the value they are using, only to a slot of
+/// their own. Unlike [`Epoch`], a reader only keeps the value it is using
+/// alive, not every value retired after it started reading. A swapped out
+/// value is freed by the first update that finds no guard referencing it.
+///
+/// The trade-off is that every update has to scan the slots of all
+/// readers, and that updates of the same [`Rcu`] serialize on the list of
+/// values waiting to be freed.
+///
+/// [`Epoch`]: super::Epoch
+/// [`Rcu`]: crate::Rcu
+#[derive(Default)]
+pub struct Hazard {
+	/// Nodes that have been retired but might still be in use.
+	garbage: Mutex<Vec<Retired>>,
+}
+
+impl Hazard {
+	/// Create a new [`Hazard`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { garbage: Mutex::new(Vec::new()) }
+	}
+
+	fn garbage(&self) -> MutexGuard<'_, Vec<Retired>> {
+		self.garbage.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+unsafe impl Reclaim for Hazard {
+	fn protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> (*mut Inner<T>, *const ()) {
+		let slot = acquire();
+
+		let mut inner = ptr.load(Ordering::Relaxed);
+		loop {
+			slot.hazard.store(inner.cast(), Ordering::SeqCst);
+
+			let x = ptr.load(Ordering::SeqCst);
+			if x == inner {
+				break;
+			}
+
+			inner = x;
+		}
+
+		(inner, ptr::from_ref(slot).cast())
+	}
+
+	unsafe fn release<T>(token: *const ()) {
+		release(&*token.cast::<Entry<Slot>>());
+	}
+
+	unsafe fn reprotect<T>(token: *const ()) -> *const () {
+		// `token` keeps the node protected until the new slot is visible to
+		// any scan after this.
+		let old = &*token.cast::<Entry<Slot>>();
+		let slot = acquire();
+		slot.hazard.store(
+			old.hazard.load(Ordering::Relaxed),
+			Ordering::SeqCst,
+		);
+
+		ptr::from_ref(slot).cast()
+	}
+
+	unsafe fn retire<T>(&self, node: *mut Inner<T>) -> *const () {
+		// `node` is protected by this slot before we scan, so the scan
+		// below cannot free it.
+		let slot = acquire();
+		slot.hazard.store(node.cast(), Ordering::Relaxed);
+
+		let mut garbage = self.garbage();
+		garbage.push(Retired::new(node));
+
+		fence(Ordering::SeqCst);
+
+		let hazards: Vec<*mut ()> = SLOTS
+			.iter()
+			.map(|s| s.hazard.load(Ordering::Acquire))
+			.filter(|x| !x.is_null())
+			.collect();
+
+		for x in
+			garbage.extract_if(.., |x| !hazards.contains(&x.node()))
+		{
+			unsafe { x.free() };
+		}
+
+		ptr::from_ref(slot).cast()
+	}
+}
+
+impl fmt::Debug for Hazard {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Hazard")
+			.field("retired", &self.garbage().len())
+			.finish()
+	}
+}
+
+impl Drop for Hazard {
+	fn drop(&mut self) {
+		// No guard can be alive anymore, see the safety section of
+		// `Reclaim`.
+		let garbage = self
+			.garbage
+			.get_mut()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		for x in garbage.drain(..) {
+			unsafe { x.free() };
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use portable_atomic::AtomicUsize;
+
+	use crate::testing::{stress, Counted};
+	use crate::Rcu;
+
+	#[test]
+	fn test_hazard() {
+		let rcu = Rcu::with_reclaim(1, Hazard::new());
+
+		let old = rcu.get();
+		rcu.update(2);
+
+		assert_eq!(*old, 1);
+		assert_eq!(*rcu.get(), 2);
+	}
+
+	#[test]
+	fn test_hazard_frees() {
+		let drops = AtomicUsize::new(0);
+		let rcu = Rcu::with_reclaim(Counted(&drops), Hazard::new());
+
+		// The value an update replaces is protected by the guard it hands
+		// back, so it is only freed by the next update.
+		for i in 1..=10 {
+			rcu.update(Counted(&drops));
+			assert_eq!(drops.load(Ordering::Relaxed), i - 1);
+		}
+
+		// A guard keeps its value alive, but not the ones after it.
+		let guard = rcu.get();
+		rcu.update(Counted(&drops));
+		rcu.update(Counted(&drops));
+		assert_eq!(drops.load(Ordering::Relaxed), 10);
+
+		drop(guard);
+		rcu.update(Counted(&drops));
+		assert_eq!(drops.load(Ordering::Relaxed), 12);
+
+		drop(rcu);
+		assert_eq!(drops.load(Ordering::Relaxed), 14);
+	}
+
+	#[test]
+	fn test_hazard_concurrent() {
+		let rcu = Rcu::with_reclaim(vec![0; 16], Hazard::new());
+		stress(
+			&rcu,
+			10_000,
+			|i| vec![i; 16],
+			|v| {
+				assert!(v.iter().all(|x| *x == v[0]));
+			},
+		);
+	}
+}
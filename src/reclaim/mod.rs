@@ -0,0 +1,288 @@
+//! Strategies for reclaiming values that have been replaced.
+//!
+//! When an [`Rcu`] is updated, the old value cannot be freed right away
+//! because readers might still be using it. A [`Reclaim`] strategy decides
+//! how readers protect the value they are using and when a value that has
+//! been swapped out is safe to free.
+//!
+//! [`Rcu`]: crate::Rcu
+
+use core::{
+	mem::ManuallyDrop,
+	ptr::{self, addr_of, addr_of_mut},
+};
+
+use alloc::boxed::Box;
+
+use portable_atomic::AtomicPtr;
+
+use crate::refs::Refs;
+
+mod deferred;
+#[cfg(feature = "std")]
+mod epoch;
+#[cfg(feature = "std")]
+mod hazard;
+mod ref_counting;
+#[cfg(feature = "std")]
+mod registry;
+
+pub use self::deferred::Deferred;
+#[cfg(feature = "std")]
+pub use self::epoch::{quiescent_state, Epoch};
+#[cfg(feature = "std")]
+pub use self::hazard::Hazard;
+pub use self::ref_counting::RefCounting;
+
+/// A value stored inside an [`Rcu`].
+///
+/// This type is opaque. It is only exposed so that [`Reclaim`] can be
+/// implemented outside of this crate, and so that allocators can query
+/// its layout to set aside memory for values ahead of time:
+///
+/// ```
+/// use core::alloc::Layout;
+///
+/// use rcurs::reclaim::Inner;
+///
+/// let layout = Layout::new::<Inner<[u8; 100]>>();
+/// assert!(layout.size() >= 100);
+/// ```
+///
+/// It is `#[repr(C)]`, so its layout only depends on that of `T`.
+///
+/// [`Rcu`]: crate::Rcu
+#[repr(C)]
+pub struct Inner<T> {
+	/// The number of active references to the specific `Inner`. Only used
+	/// by [`RefCounting`].
+	///
+	/// This comes first so that `data` always starts on the cache line
+	/// after it.
+	pub(crate) refs: Refs,
+	/// The data.
+	pub(crate) data: T,
+}
+
+impl<T> Inner<T> {
+	/// Allocate a new node holding `data`.
+	///
+	/// The node is leaked unless it is installed in an [`Rcu`] or freed
+	/// with [`Inner::free`].
+	///
+	/// [`Rcu`]: crate::Rcu
+	pub fn new(data: T) -> *mut Self {
+		alloc(data)
+	}
+
+	/// Free `this`.
+	///
+	/// # Safety
+	///
+	/// No reader may be using `this`. Either it has never been installed
+	/// in an [`Rcu`], or it has been handed over to [`Reclaim::retire`] and
+	/// all readers are gone. It must not be freed more than once.
+	///
+	/// [`Rcu`]: crate::Rcu
+	pub unsafe fn free(this: *mut Self) {
+		drop(Box::from_raw(this));
+	}
+
+	/// Get a pointer to the data inside `this`.
+	pub(crate) const unsafe fn data(this: *const Self) -> *const T {
+		addr_of!((*this).data)
+	}
+}
+
+/// A node that has been retired but might still be in use by readers.
+pub(crate) struct Retired {
+	node: *mut (),
+	/// Free `node`.
+	free: unsafe fn(*mut ()),
+}
+
+// Retired nodes are freed by whichever thread gets to them first, not
+// necessarily the one that retired them.
+unsafe impl Send for Retired {}
+
+impl Retired {
+	pub(crate) fn new<T>(node: *mut Inner<T>) -> Self {
+		Self { node: node.cast(), free: free::<T> }
+	}
+
+	#[cfg(feature = "std")]
+	pub(crate) const fn node(&self) -> *mut () {
+		self.node
+	}
+
+	/// Get the refs of the node.
+	///
+	/// `refs` is the first field of the `#[repr(C)]` `Inner`, so it can be
+	/// reached without knowing the type of the data.
+	pub(crate) const fn refs(&self) -> &Refs {
+		unsafe { &*self.node.cast::<Refs>() }
+	}
+
+	/// Free the node.
+	///
+	/// No reader may be using it anymore.
+	pub(crate) unsafe fn free(self) {
+		(self.free)(self.node);
+	}
+}
+
+unsafe fn free<T>(node: *mut ()) {
+	Inner::free(node.cast::<Inner<T>>());
+}
+
+pub(crate) fn alloc<T>(data: T) -> *mut Inner<T> {
+	Box::into_raw(Box::new(Inner { refs: Refs::one(), data }))
+}
+
+/// Like [`alloc`], but moves `data` straight from its box into the new
+/// node instead of through the stack.
+pub(crate) fn alloc_boxed<T>(data: Box<T>) -> *mut Inner<T> {
+	let mut node = Box::<Inner<T>>::new_uninit();
+	let node_ptr = node.as_mut_ptr();
+	let data = Box::into_raw(data);
+
+	unsafe {
+		addr_of_mut!((*node_ptr).refs).write(Refs::one());
+		ptr::copy_nonoverlapping(
+			data,
+			addr_of_mut!((*node_ptr).data),
+			1,
+		);
+
+		// The value has been moved out, only free the memory.
+		drop(Box::from_raw(data.cast::<ManuallyDrop<T>>()));
+
+		Box::into_raw(node.assume_init())
+	}
+}
+
+/// Free `x` and return the data it held.
+pub(crate) unsafe fn into_data<T>(x: *mut Inner<T>) -> T {
+	Box::from_raw(x).data
+}
+
+/// A strategy for reclaiming the values of an [`Rcu`].
+///
+/// # Safety
+///
+/// Implementations must make sure that a node returned by [`protect`] or
+/// [`retire`] is not freed before the token returned alongside it is passed
+/// to [`release`]. Every node handed over to [`retire`] must eventually be
+/// freed exactly once, at the latest when the strategy itself is dropped.
+///
+/// A strategy is only dropped when the [`Rcu`] it belongs to is dropped
+/// or taken apart. Guards borrow the [`Rcu`], so none of them can be alive
+/// anymore by then. The strategy may free the nodes it still holds
+/// without waiting for their tokens to be released.
+///
+/// [`Rcu`]: crate::Rcu
+/// [`protect`]: Self::protect
+/// [`retire`]: Self::retire
+/// [`release`]: Self::release
+pub unsafe trait Reclaim {
+	/// Load the node stored in `ptr` and protect it from being freed.
+	///
+	/// Returns the node along with a token that must be passed to
+	/// [`release`] once the node is not in use anymore.
+	///
+	/// [`release`]: Self::release
+	fn protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> (*mut Inner<T>, *const ());
+
+	/// Drop the protection represented by `token`.
+	///
+	/// # Safety
+	///
+	/// `token` must have been returned by [`protect`] or [`retire`] of this
+	/// strategy for a node of type `Inner<T>`. It must not be used again.
+	///
+	/// [`protect`]: Self::protect
+	/// [`retire`]: Self::retire
+	unsafe fn release<T>(token: *const ());
+
+	/// Protect the node that `token` protects once more.
+	///
+	/// Returns a new token, independent of `token`, that must be passed to
+	/// [`release`] as well.
+	///
+	/// # Safety
+	///
+	/// `token` must have been returned by [`protect`], [`retire`] or
+	/// [`reprotect`] of this strategy for a node of type `Inner<T>` and must
+	/// not have been released yet.
+	///
+	/// [`protect`]: Self::protect
+	/// [`retire`]: Self::retire
+	/// [`reprotect`]: Self::reprotect
+	/// [`release`]: Self::release
+	unsafe fn reprotect<T>(token: *const ()) -> *const ();
+
+	/// Make room to [`retire`] one more node.
+	///
+	/// Updates call this before they swap in a new node. Returns `false`
+	/// if [`retire`] could only make room by waiting for readers to finish
+	/// or by freeing nodes. Every call that returns `true` must be followed
+	/// by one call to [`retire`] or [`unreserve`].
+	///
+	/// The default implementation always returns `true`.
+	///
+	/// [`retire`]: Self::retire
+	/// [`unreserve`]: Self::unreserve
+	fn reserve(&self) -> bool {
+		true
+	}
+
+	/// Give back the room made by a call to [`reserve`] that is not
+	/// followed by a call to [`retire`].
+	///
+	/// The default implementation does nothing.
+	///
+	/// [`reserve`]: Self::reserve
+	/// [`retire`]: Self::retire
+	fn unreserve(&self) {}
+
+	/// Retire `node` so that it gets freed once no reader is using it.
+	///
+	/// Returns a token that keeps `node` protected, as if it had been
+	/// returned by [`protect`]. It must be passed to [`release`].
+	///
+	/// If no room was made for `node` with [`reserve`], this may fall back
+	/// to something slower.
+	///
+	/// # Safety
+	///
+	/// `node` must have been swapped out of the pointer that [`protect`] is
+	/// called with, so that no new readers can get to it. It must not be
+	/// retired more than once.
+	///
+	/// [`protect`]: Self::protect
+	/// [`release`]: Self::release
+	/// [`reserve`]: Self::reserve
+	unsafe fn retire<T>(&self, node: *mut Inner<T>) -> *const ();
+
+	/// Take the data out of the node protected by `token` if no one else
+	/// is using it, and free the node.
+	///
+	/// Returns [`None`] and leaves `token` untouched otherwise. The default
+	/// implementation always returns [`None`].
+	///
+	/// # Safety
+	///
+	/// `token` must have been returned by [`retire`] of this strategy for a
+	/// node of type `Inner<T>`. If this returns [`Some`], `token` must not
+	/// be used again.
+	///
+	/// [`retire`]: Self::retire
+	#[must_use]
+	unsafe fn try_unwrap<T>(token: *const ()) -> Option<T> {
+		let _ = token;
+		None
+	}
+}
@@ -0,0 +1,190 @@
+use core::hint;
+
+use portable_atomic::{fence, AtomicPtr, AtomicUsize, Ordering};
+
+use super::{into_data, Inner, Reclaim};
+use crate::refs::{Refs, RefsOverflow};
+
+/// Reclaim values by counting the references to each of them.
+///
+/// Every guard holds a reference to the value it was created for, and the
+/// [`Rcu`] holds one to its current value. A value is freed as soon as the
+/// last reference to it goes away.
+///
+/// This is the default strategy.
+///
+/// # Progress
+///
+/// Between loading the pointer and taking its ref, a reader is not
+/// protected by any ref yet. Readers announce this window in a counter
+/// that is shared by all readers of the [`Rcu`]. That costs every `get`
+/// two `SeqCst` read-modify-writes on that one cache line. After swapping
+/// in a new value, an update spins until the counter drops to zero, so
+/// that the old value is not freed under a reader that is about to take
+/// a ref to it:
+///
+/// - The counter only drops to zero when no reader is in the window. If
+///   new readers keep entering it before the previous ones leave, the
+///   update keeps waiting.
+/// - A reader that is preempted inside the window holds up every update
+///   until it runs again.
+/// - On a single core, an update from an interrupt handler that
+///   interrupted a reader inside the window never returns.
+///
+/// Readers never wait. With the `std` feature, the `Epoch` and `Hazard`
+/// strategies offer updates that do not wait for readers either.
+///
+/// [`Rcu`]: crate::Rcu
+#[derive(Debug, Default)]
+pub struct RefCounting {
+	/// The number of [`protect`] calls that have loaded the pointer but
+	/// have not yet taken a ref to the `Inner` it points to.
+	///
+	/// [`protect`]: Reclaim::protect
+	loading: AtomicUsize,
+}
+
+impl RefCounting {
+	/// Same as [`protect`], but returns an error instead of panicking if
+	/// the ref count would overflow.
+	///
+	/// [`protect`]: Reclaim::protect
+	pub(crate) fn try_protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> Result<(*mut Inner<T>, *const ()), RefsOverflow> {
+		self.protect_with(ptr, Refs::checked_take_ref)
+	}
+
+	fn protect_with<T, E>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+		take_ref: impl FnOnce(&Refs) -> Result<(), E>,
+	) -> Result<(*mut Inner<T>, *const ()), E> {
+		// Between loading `ptr` and taking a ref, nothing keeps the `Inner`
+		// alive. Announce that we are in this window so `retire` does not
+		// release the `Inner` from under us.
+		let loading = Loading::enter(&self.loading);
+		// Pairs with the fence in `retire`. Either we load the pointer that
+		// replaced the old one, or `retire` sees us in `loading`.
+		fence(Ordering::SeqCst);
+		let inner = ptr.load(Ordering::SeqCst);
+		let r = take_ref(unsafe { &(*inner).refs });
+		drop(loading);
+
+		r.map(|()| (inner, inner.cast_const().cast()))
+	}
+}
+
+/// A reader inside the window announced in [`RefCounting::loading`].
+///
+/// The reader leaves the window when this is dropped, even if taking the
+/// ref panicked. Otherwise, every later update would wait for it forever.
+struct Loading<'a>(&'a AtomicUsize);
+
+impl<'a> Loading<'a> {
+	fn enter(loading: &'a AtomicUsize) -> Self {
+		loading.fetch_add(1, Ordering::SeqCst);
+		Self(loading)
+	}
+}
+
+impl Drop for Loading<'_> {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::Release);
+	}
+}
+
+unsafe impl Reclaim for RefCounting {
+	fn protect<T>(
+		&self,
+		ptr: &AtomicPtr<Inner<T>>,
+	) -> (*mut Inner<T>, *const ()) {
+		let r = self.protect_with(ptr, |refs| {
+			refs.take_ref();
+			Ok::<_, core::convert::Infallible>(())
+		});
+
+		match r {
+			Ok(x) => x,
+		}
+	}
+
+	unsafe fn release<T>(token: *const ()) {
+		let inner = token.cast::<Inner<T>>().cast_mut();
+
+		if Refs::release_ref(&raw const (*inner).refs) {
+			Inner::free(inner);
+		}
+	}
+
+	unsafe fn reprotect<T>(token: *const ()) -> *const () {
+		(*token.cast::<Inner<T>>()).refs.take_ref();
+		token
+	}
+
+	/// Any `protect` call that has not yet announced itself in `loading`
+	/// by the time we observe it to be zero is guaranteed to load the new
+	/// pointer. The ones that have, must finish taking their ref before we
+	/// can release ours, otherwise `node` might be freed before they get to
+	/// it.
+	///
+	/// The ref the [`Rcu`] had to `node` is handed over to the returned
+	/// token.
+	///
+	/// [`Rcu`]: crate::Rcu
+	unsafe fn retire<T>(&self, node: *mut Inner<T>) -> *const () {
+		// Pairs with the fence in `protect_with`, so the swap of the
+		// pointer is ordered before the loads of `loading`.
+		fence(Ordering::SeqCst);
+		while self.loading.load(Ordering::SeqCst) != 0 {
+			hint::spin_loop();
+		}
+
+		node.cast_const().cast()
+	}
+
+	unsafe fn try_unwrap<T>(token: *const ()) -> Option<T> {
+		let inner = token.cast::<Inner<T>>().cast_mut();
+
+		// A retired node cannot be reached through the `Rcu` anymore, so no
+		// new refs to it can appear.
+		if (*inner).refs.count() != 1 {
+			return None;
+		}
+
+		// Synchronize with the release of the other refs so we see all
+		// accesses to `data` made through them.
+		fence(Ordering::Acquire);
+		Some(into_data(inner))
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::panic::{catch_unwind, AssertUnwindSafe};
+
+	use crate::reclaim::alloc;
+
+	#[test]
+	fn test_protect_panics() {
+		let reclaim = RefCounting::default();
+		let ptr = AtomicPtr::new(alloc(1));
+
+		let r = catch_unwind(AssertUnwindSafe(|| {
+			reclaim.protect_with(&ptr, |_| -> Result<(), ()> {
+				panic!("ref count overflow")
+			})
+		}));
+		assert!(r.is_err());
+
+		// Otherwise, `retire` would spin forever.
+		assert_eq!(reclaim.loading.load(Ordering::Relaxed), 0);
+
+		let node = ptr.load(Ordering::Relaxed);
+		let token = unsafe { reclaim.retire(node) };
+		unsafe { RefCounting::release::<i32>(token) };
+	}
+}
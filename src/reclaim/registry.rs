@@ -0,0 +1,110 @@
+use core::{ops::Deref, ptr};
+
+use alloc::boxed::Box;
+
+use portable_atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// A lock-free list of records that are never freed.
+///
+/// A record is owned by one user at a time. It is reused by the next
+/// [`acquire`] after its owner [`release`]s it, so the list only grows to
+/// the largest number of records that were in use at the same time.
+///
+/// [`acquire`]: Self::acquire
+/// [`release`]: Entry::release
+pub struct Registry<T> {
+	head: AtomicPtr<Entry<T>>,
+}
+
+/// A record in a [`Registry`].
+pub struct Entry<T> {
+	value: T,
+	/// Whether this entry is owned by someone.
+	in_use: AtomicBool,
+	/// The next entry in the list. Never changes after the entry has been
+	/// published.
+	next: *const Self,
+}
+
+// `next` is never written after the entry has been published.
+unsafe impl<T: Sync> Sync for Entry<T> {}
+
+impl<T: 'static> Registry<T> {
+	pub const fn new() -> Self {
+		Self { head: AtomicPtr::new(ptr::null_mut()) }
+	}
+
+	/// Take ownership of an unused entry, or register a new one holding
+	/// the value `init` returns.
+	///
+	/// A reused entry keeps the value of its previous owner.
+	pub fn acquire(
+		&self,
+		init: impl FnOnce() -> T,
+	) -> &'static Entry<T> {
+		for x in self.iter() {
+			let r = x.in_use.compare_exchange(
+				false,
+				true,
+				Ordering::Acquire,
+				Ordering::Relaxed,
+			);
+
+			if r.is_ok() {
+				return x;
+			}
+		}
+
+		// Stick to the raw pointer until the entry is published. Writing
+		// `next` through a `&mut` would invalidate the pointer in the list.
+		let x = Box::into_raw(Box::new(Entry {
+			value: init(),
+			in_use: AtomicBool::new(true),
+			next: ptr::null(),
+		}));
+
+		let mut head = self.head.load(Ordering::Relaxed);
+		loop {
+			// Nobody else can see `x` until it is published.
+			unsafe { (*x).next = head };
+
+			let r = self.head.compare_exchange_weak(
+				head,
+				x,
+				Ordering::Release,
+				Ordering::Relaxed,
+			);
+
+			match r {
+				Ok(_) => return unsafe { &*x },
+				Err(h) => head = h,
+			}
+		}
+	}
+
+	/// Iterate over all entries, used or not.
+	pub fn iter(&self) -> impl Iterator<Item = &'static Entry<T>> {
+		let mut x = self.head.load(Ordering::Acquire).cast_const();
+
+		core::iter::from_fn(move || {
+			let e = unsafe { x.as_ref()? };
+			x = e.next;
+			Some(e)
+		})
+	}
+}
+
+impl<T> Entry<T> {
+	/// Give up ownership of the entry.
+	pub fn release(&self) {
+		self.in_use.store(false, Ordering::Release);
+	}
+}
+
+impl<T> Deref for Entry<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
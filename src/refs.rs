@@ -1,8 +1,16 @@
-use portable_atomic::{AtomicUsize, Ordering};
+use core::fmt;
+
+use portable_atomic::{fence, AtomicUsize, Ordering};
 
 const REF_COUNT_MAX: usize = usize::MAX;
 
+/// A reference count.
+///
+/// Every reader updates the reference count, while the data next to it
+/// is only read. The count is aligned to its own cache line so that
+/// updating it does not evict the data from the caches of other readers.
 #[derive(Debug)]
+#[repr(align(64))]
 pub struct Refs {
 	refs: AtomicUsize,
 }
@@ -18,6 +26,12 @@ impl Refs {
 	}
 
 	/// Increment the ref count by one.
+	///
+	/// This can be relaxed, as taking a ref always happens through an
+	/// existing one, or while the [`Reclaim`] strategy keeps the `Inner`
+	/// alive.
+	///
+	/// [`Reclaim`]: crate::reclaim::Reclaim
 	pub fn take_ref(&self) {
 		let r = self.refs.fetch_add(1, Ordering::Relaxed);
 
@@ -26,13 +40,38 @@ impl Refs {
 		}
 	}
 
+	/// Increment the ref count by one, unless that would overflow it.
+	///
+	/// # Errors
+	///
+	/// Returns [`RefsOverflow`] if the ref count is already at its maximum.
+	pub fn checked_take_ref(&self) -> Result<(), RefsOverflow> {
+		self.refs
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+				x.checked_add(1)
+			})
+			.map(|_| ())
+			.map_err(|_| RefsOverflow)
+	}
+
 	/// Decrement the ref count by one.
 	///
 	/// Returns `true` if this ref was the last one. Otherwise it returns `false`.
-	pub unsafe fn release_ref(&self) -> bool {
-		let r = self.refs.fetch_sub(1, Ordering::Release);
+	///
+	/// This takes a pointer instead of `&self`, because as soon as the count
+	/// is decremented, another thread might free `this` while the reference
+	/// would still be live.
+	pub unsafe fn release_ref(this: *const Self) -> bool {
+		let refs = &(*this).refs;
+
+		// Every release publishes the accesses made through its ref. The
+		// last one must see all of them before the data is dropped, so it
+		// acquires the release sequence of all previous ones. The other
+		// releases never access the data again, so they have nothing to
+		// acquire. This is the same protocol as `Arc`.
+		let r = refs.fetch_sub(1, Ordering::Release);
 		if r == 1 {
-			let _ = self.refs.load(Ordering::Acquire);
+			fence(Ordering::Acquire);
 			true
 		} else if r == 0 {
 			panic_ref_count_overflow()
@@ -42,6 +81,19 @@ impl Refs {
 	}
 }
 
+/// The error returned when a value has too many references to take
+/// another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefsOverflow;
+
+impl fmt::Display for RefsOverflow {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("ref count overflowed")
+	}
+}
+
+impl core::error::Error for RefsOverflow {}
+
 #[cold]
 #[inline(never)]
 fn panic_ref_count_overflow() -> ! {
@@ -0,0 +1,168 @@
+use core::fmt;
+
+#[cfg(debug_assertions)]
+use portable_atomic::{AtomicBool, Ordering};
+
+use crate::rcu::{Guard, OldGuard, Rcu};
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// An [`Rcu`] that checks that it is only updated by one writer at a
+/// time.
+///
+/// Concurrent updates of an [`Rcu`] are allowed, but with [`update`] the
+/// last one wins and the others are lost. Code that expects to be the only
+/// writer can use this wrapper to catch the mistake. In debug builds,
+/// every update panics if another one is still running. In release builds
+/// the check is compiled out and this is just an [`Rcu`].
+///
+/// See: [`Rcu::with_single_writer_check`].
+///
+/// [`update`]: Rcu::update
+pub struct SingleWriterRcu<T, R: Reclaim = RefCounting> {
+	rcu: Rcu<T, R>,
+	/// Whether an update is running.
+	#[cfg(debug_assertions)]
+	writing: AtomicBool,
+}
+
+impl<T> Rcu<T> {
+	/// Create a new [`SingleWriterRcu`] with an initial value of `data`.
+	pub fn with_single_writer_check(data: T) -> SingleWriterRcu<T> {
+		SingleWriterRcu::from(Self::new(data))
+	}
+}
+
+impl<T, R: Reclaim> SingleWriterRcu<T, R> {
+	/// Get the value inside the [`Rcu`].
+	///
+	/// See: [`Rcu::get`].
+	pub fn get(&self) -> Guard<'_, T> {
+		self.rcu.get()
+	}
+
+	/// Update the value inside the [`Rcu`].
+	///
+	/// See: [`Rcu::update`].
+	///
+	/// # Panics
+	///
+	/// In debug builds, if another update is running at the same time.
+	pub fn update(&self, new: T) {
+		self.write(|| self.rcu.update(new));
+	}
+
+	/// Update the value inside the [`Rcu`] and return a guard to the old
+	/// one.
+	///
+	/// See: [`Rcu::replace`].
+	///
+	/// # Panics
+	///
+	/// In debug builds, if another update is running at the same time.
+	pub fn replace(&self, new: T) -> OldGuard<'_, T> {
+		self.write(|| self.rcu.replace(new))
+	}
+
+	/// Update the value inside the [`Rcu`] based on the current one.
+	///
+	/// See: [`Rcu::update_with`].
+	///
+	/// # Panics
+	///
+	/// In debug builds, if another update is running at the same time.
+	pub fn update_with<F>(&self, f: F) -> bool
+	where
+		T: PartialEq,
+		F: FnMut(&T) -> T,
+	{
+		self.write(|| self.rcu.update_with(f))
+	}
+
+	/// Get the value inside the [`Rcu`] along with its version.
+	///
+	/// See: [`Rcu::get_version`].
+	pub fn get_version(&self) -> (Guard<'_, T>, u64) {
+		self.rcu.get_version()
+	}
+
+	/// Get the number of times the value has been replaced.
+	///
+	/// See: [`Rcu::generation`].
+	pub fn generation(&self) -> u64 {
+		self.rcu.generation()
+	}
+
+	/// Take the [`Rcu`] out of the wrapper.
+	pub fn into_rcu(self) -> Rcu<T, R> {
+		self.rcu
+	}
+
+	#[cfg(debug_assertions)]
+	fn write<U>(&self, f: impl FnOnce() -> U) -> U {
+		struct Writing<'a>(&'a AtomicBool);
+
+		impl Drop for Writing<'_> {
+			fn drop(&mut self) {
+				self.0.store(false, Ordering::Release);
+			}
+		}
+
+		assert!(
+			!self.writing.swap(true, Ordering::Acquire),
+			"concurrent update detected — use a separate writer lock"
+		);
+
+		let _writing = Writing(&self.writing);
+		f()
+	}
+
+	#[cfg(not(debug_assertions))]
+	#[allow(clippy::unused_self)]
+	fn write<U>(&self, f: impl FnOnce() -> U) -> U {
+		f()
+	}
+}
+
+impl<T, R: Reclaim> From<Rcu<T, R>> for SingleWriterRcu<T, R> {
+	fn from(rcu: Rcu<T, R>) -> Self {
+		Self {
+			rcu,
+			#[cfg(debug_assertions)]
+			writing: AtomicBool::new(false),
+		}
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug for SingleWriterRcu<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&self.rcu, f)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_single_writer() {
+		let rcu = Rcu::with_single_writer_check(0);
+
+		rcu.update(1);
+		assert!(rcu.update_with(|x| x + 1));
+		assert_eq!(*rcu.replace(3), 2);
+		assert_eq!(*rcu.get(), 3);
+		assert_eq!(rcu.generation(), 3);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic = "concurrent update detected"]
+	fn test_single_writer_concurrent() {
+		let rcu = Rcu::with_single_writer_check(0);
+
+		rcu.update_with(|x| {
+			rcu.update(*x);
+			x + 1
+		});
+	}
+}
@@ -0,0 +1,65 @@
+use core::{fmt, ops::Deref};
+
+use crate::rcu::Guard;
+
+/// An owned copy of the value of an [`Rcu`] at some point in time.
+///
+/// Unlike a [`Guard`], a [`Snapshot`] does not borrow the [`Rcu`] it was
+/// taken from and does not keep anything inside it alive. It can be
+/// stored or sent anywhere. It never reflects later updates to the
+/// [`Rcu`].
+///
+/// [`Rcu`]: crate::Rcu
+#[derive(
+	Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct Snapshot<T>(T);
+
+impl<T> Snapshot<T> {
+	/// Take the value out of the snapshot.
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+/// Clone the value out of the guard.
+impl<T: Clone> From<Guard<'_, T>> for Snapshot<T> {
+	fn from(guard: Guard<'_, T>) -> Self {
+		Self(Guard::into_owned(guard))
+	}
+}
+
+impl<T> Deref for Snapshot<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<T: fmt::Display> fmt::Display for Snapshot<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&self.0, f)
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	use std::thread;
+
+	use crate::Rcu;
+
+	#[test]
+	fn test_snapshot() {
+		let rcu = Rcu::new(1);
+
+		let snapshot = Snapshot::from(rcu.get());
+		rcu.update(2);
+
+		let t = thread::spawn(move || *snapshot);
+		assert_eq!(t.join().unwrap(), 1);
+		assert_eq!(rcu.reader_count(), 0);
+	}
+}
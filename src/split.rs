@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// Split a shared [`Rcu`] into a reading and a writing handle.
+///
+/// Functions that only need to read the value can take an [`RcuReader`],
+/// which makes it obvious that they never update it. There is only ever
+/// one [`RcuWriter`], which makes it easy to see where updates come from.
+///
+/// Other clones of `rcu` can still be used to update the value. Split an
+/// `Arc` that is not shared with anything else to get the guarantee of a
+/// single writer.
+pub fn split<T, R: Reclaim>(
+	rcu: Arc<Rcu<T, R>>,
+) -> (RcuReader<T, R>, RcuWriter<T, R>) {
+	(RcuReader { rcu: Arc::clone(&rcu) }, RcuWriter { rcu })
+}
+
+/// The reading handle of an [`Rcu`].
+///
+/// See: [`split`].
+pub struct RcuReader<T, R: Reclaim = RefCounting> {
+	rcu: Arc<Rcu<T, R>>,
+}
+
+impl<T, R: Reclaim> RcuReader<T, R> {
+	/// Get the value inside the [`Rcu`].
+	///
+	/// See: [`Rcu::get`].
+	#[must_use]
+	pub fn get(&self) -> Guard<'_, T> {
+		self.rcu.get()
+	}
+}
+
+impl<T, R: Reclaim> Clone for RcuReader<T, R> {
+	fn clone(&self) -> Self {
+		Self { rcu: Arc::clone(&self.rcu) }
+	}
+}
+
+/// The writing handle of an [`Rcu`].
+///
+/// See: [`split`].
+pub struct RcuWriter<T, R: Reclaim = RefCounting> {
+	rcu: Arc<Rcu<T, R>>,
+}
+
+impl<T, R: Reclaim> RcuWriter<T, R> {
+	/// Update the value inside the [`Rcu`].
+	///
+	/// See: [`Rcu::update`].
+	pub fn update(&self, new: T) {
+		self.rcu.update(new);
+	}
+
+	/// Update the value inside the [`Rcu`] based on its current value.
+	///
+	/// See: [`Rcu::update_with`].
+	pub fn update_with<F>(&self, f: F) -> bool
+	where
+		T: PartialEq,
+		F: FnMut(&T) -> T,
+	{
+		self.rcu.update_with(f)
+	}
+
+	/// Get the value inside the [`Rcu`].
+	///
+	/// See: [`Rcu::get`].
+	#[must_use]
+	pub fn get(&self) -> Guard<'_, T> {
+		self.rcu.get()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::thread;
+
+	#[test]
+	fn test_split() {
+		let (reader, writer) = split(Arc::new(Rcu::new(0)));
+
+		let r = reader.clone();
+		let t = thread::spawn(move || {
+			let mut last = 0;
+			while last != 10 {
+				last = *r.get();
+			}
+		});
+
+		for i in 1..=10 {
+			writer.update(i);
+		}
+		t.join().unwrap();
+
+		drop(writer);
+		assert_eq!(*reader.get(), 10);
+	}
+}
@@ -0,0 +1,99 @@
+//! Helpers shared by the tests of the different kinds of [`Rcu`].
+
+use std::sync::Barrier;
+use std::thread::scope;
+
+use portable_atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::reclaim::Reclaim;
+use crate::{CopyRcu, InlineRcu, Plain, Rcu};
+
+/// A value that counts how often it has been dropped.
+pub struct Counted<'a>(pub &'a AtomicUsize);
+
+impl Drop for Counted<'_> {
+	fn drop(&mut self) {
+		self.0.fetch_add(1, Ordering::Relaxed);
+	}
+}
+
+/// A value that can be read and updated from many threads at once.
+pub trait Shared<T>: Sync {
+	fn read(&self, f: impl FnOnce(&T));
+
+	fn write(&self, new: T);
+}
+
+impl<T: Send + Sync, R: Reclaim + Sync> Shared<T> for Rcu<T, R> {
+	fn read(&self, f: impl FnOnce(&T)) {
+		f(&self.get());
+	}
+
+	fn write(&self, new: T) {
+		self.update(new);
+	}
+}
+
+impl<T: Send + Sync, const SLOTS: usize> Shared<T>
+	for InlineRcu<T, SLOTS>
+{
+	fn read(&self, f: impl FnOnce(&T)) {
+		f(&self.get());
+	}
+
+	fn write(&self, new: T) {
+		self.update(new);
+	}
+}
+
+impl<T: Plain + Send> Shared<T> for CopyRcu<T> {
+	fn read(&self, f: impl FnOnce(&T)) {
+		f(&self.get_copy());
+	}
+
+	fn write(&self, new: T) {
+		self.update(new);
+	}
+}
+
+/// The number of threads [`stress`] reads with.
+pub const READERS: usize = 8;
+
+/// Update `rcu` with `make_value(1..=updates)` while [`READERS`] threads
+/// keep reading it and call `check` on every value they see.
+///
+/// A value that is freed too early is very likely to be reused by the next
+/// update, so `check` should look for torn values.
+pub fn stress<T, S: Shared<T>>(
+	rcu: &S,
+	updates: usize,
+	mut make_value: impl FnMut(usize) -> T,
+	check: impl Fn(&T) + Sync,
+) {
+	let updates = if cfg!(miri) {
+		100
+	} else {
+		updates
+	};
+
+	let start = Barrier::new(READERS + 1);
+	let done = AtomicBool::new(false);
+
+	scope(|scope| {
+		for _ in 0..READERS {
+			scope.spawn(|| {
+				start.wait();
+				while !done.load(Ordering::Relaxed) {
+					rcu.read(&check);
+				}
+			});
+		}
+
+		start.wait();
+		for i in 1..=updates {
+			rcu.write(make_value(i));
+		}
+
+		done.store(true, Ordering::Relaxed);
+	});
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// A read-only view of a component of the value of an [`Rcu`].
+///
+/// This hides the type of the whole value from code that only needs a
+/// part of it. Every [`get`] gets the current value of the [`Rcu`] and
+/// [maps] it to the component.
+///
+/// See: [`Rcu::view`].
+///
+/// [`get`]: Self::get
+/// [maps]: Guard::map
+pub struct RcuView<'a, T, U: ?Sized, R: Reclaim = RefCounting> {
+	rcu: &'a Rcu<T, R>,
+	project: fn(&T) -> &U,
+}
+
+impl<T, R: Reclaim> Rcu<T, R> {
+	/// Create a view of the component of the value that `project`
+	/// returns.
+	///
+	/// See: [`RcuView`].
+	pub fn view<U: ?Sized>(
+		&self,
+		project: fn(&T) -> &U,
+	) -> RcuView<'_, T, U, R> {
+		RcuView { rcu: self, project }
+	}
+}
+
+impl<'a, T: Send + Sync, U: ?Sized, R: Reclaim> RcuView<'a, T, U, R> {
+	/// Get the component of the current value.
+	#[must_use]
+	pub fn get(&self) -> Guard<'a, U> {
+		Guard::map(self.rcu.get(), self.project)
+	}
+}
+
+impl<T, U: ?Sized, R: Reclaim> Clone for RcuView<'_, T, U, R> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T, U: ?Sized, R: Reclaim> Copy for RcuView<'_, T, U, R> {}
+
+impl<T, U, R> fmt::Debug for RcuView<'_, T, U, R>
+where
+	T: Send + Sync,
+	U: ?Sized + fmt::Debug,
+	R: Reclaim,
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("RcuView").field(&&*self.get()).finish()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	struct Config {
+		name: String,
+		port: u16,
+	}
+
+	#[test]
+	fn test_view() {
+		let config =
+			Rcu::new(Config { name: "a".to_owned(), port: 1 });
+
+		let name = config.view(|x| x.name.as_str());
+		let port = config.view(|x| &x.port);
+
+		let old = name.get();
+		config.update(Config { name: "b".to_owned(), port: 2 });
+
+		assert_eq!(&*old, "a");
+		assert_eq!(&*name.get(), "b");
+		assert_eq!(*port.get(), 2);
+	}
+}
@@ -0,0 +1,208 @@
+use alloc::vec::Vec;
+
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use portable_atomic::{fence, AtomicUsize, Ordering};
+
+use crate::rcu::{Guard, Rcu};
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// The senders of all [`Watch`]es of an [`Rcu`].
+#[derive(Debug, Default)]
+pub struct Watchers {
+	state: Mutex<State>,
+	/// The number of registered watchers, so updates can skip `state` if
+	/// there are none.
+	count: AtomicUsize,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	/// The id of the next watcher.
+	next: usize,
+	senders: Vec<(usize, SyncSender<()>)>,
+}
+
+impl Watchers {
+	pub const fn new() -> Self {
+		Self {
+			state: Mutex::new(State { next: 0, senders: Vec::new() }),
+			count: AtomicUsize::new(0),
+		}
+	}
+
+	/// Register a new watcher.
+	///
+	/// Returns the id to [`remove`] it with.
+	///
+	/// [`remove`]: Self::remove
+	pub fn add(&self) -> (usize, Receiver<()>) {
+		// A capacity of one is enough. If a notification is already pending,
+		// the watcher is going to load the latest value anyway.
+		let (tx, rx) = mpsc::sync_channel(1);
+
+		let mut state = self.lock();
+		let id = state.next;
+		state.next += 1;
+		state.senders.push((id, tx));
+		self.count.store(state.senders.len(), Ordering::Relaxed);
+		drop(state);
+
+		// Pairs with the fence in `notify`. Either the watcher loads the
+		// value stored by an update, or the update sees the watcher.
+		fence(Ordering::SeqCst);
+		(id, rx)
+	}
+
+	/// Unregister the watcher with `id`.
+	pub fn remove(&self, id: usize) {
+		let mut state = self.lock();
+		state.senders.retain(|(x, _)| *x != id);
+		self.count.store(state.senders.len(), Ordering::Relaxed);
+	}
+
+	/// Notify all watchers that the value has changed.
+	pub fn notify(&self) {
+		fence(Ordering::SeqCst);
+		if self.count.load(Ordering::Relaxed) == 0 {
+			return;
+		}
+
+		let mut state = self.lock();
+		state.senders.retain(|(_, tx)| match tx.try_send(()) {
+			Ok(()) | Err(TrySendError::Full(())) => true,
+			Err(TrySendError::Disconnected(())) => false,
+		});
+		self.count.store(state.senders.len(), Ordering::Relaxed);
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// A receiver of changes to the value of an [`Rcu`].
+///
+/// Changes are lossy. If the value is updated many times before the
+/// watcher gets to it, the watcher only sees the latest value, once.
+/// A watcher never misses the latest value though.
+///
+/// A [`Watch`] is also an endless [`Iterator`] over the changed values.
+///
+/// See: [`Rcu::watch`].
+#[derive(Debug)]
+pub struct Watch<'a, T, R: Reclaim = RefCounting> {
+	rcu: &'a Rcu<T, R>,
+	/// The id of this watcher in the [`Watchers`] of `rcu`.
+	id: usize,
+	rx: Receiver<()>,
+}
+
+impl<'a, T, R: Reclaim> Watch<'a, T, R> {
+	pub(crate) fn new(rcu: &'a Rcu<T, R>) -> Self {
+		let (id, rx) = rcu.watchers.add();
+		Self { rcu, id, rx }
+	}
+
+	/// Wait until the value changes and get the new one.
+	///
+	/// Returns right away if the value has changed since the last call.
+	#[must_use]
+	pub fn changed(&self) -> Guard<'a, T> {
+		// The sender lives in `rcu`, which outlives us, so this can only
+		// return once a notification arrives.
+		let _ = self.rx.recv();
+		self.rcu.get()
+	}
+
+	/// Get the new value if it has changed since the last call.
+	///
+	/// This function does _not_ block execution.
+	#[must_use]
+	pub fn try_changed(&self) -> Option<Guard<'a, T>> {
+		self.rx.try_recv().ok().map(|()| self.rcu.get())
+	}
+}
+
+impl<T, R: Reclaim> Drop for Watch<'_, T, R> {
+	fn drop(&mut self) {
+		self.rcu.watchers.remove(self.id);
+	}
+}
+
+impl<'a, T, R: Reclaim> Iterator for Watch<'a, T, R> {
+	type Item = Guard<'a, T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		Some(self.changed())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::sync::Barrier;
+	use std::thread::scope;
+
+	#[test]
+	fn test_watch() {
+		let rcu = Rcu::new(0);
+		let watch = rcu.watch();
+
+		assert!(watch.try_changed().is_none());
+
+		rcu.update(1);
+		rcu.update(2);
+		assert_eq!(*watch.changed(), 2);
+		assert!(watch.try_changed().is_none());
+	}
+
+	#[test]
+	fn test_watch_dropped() {
+		let rcu = Rcu::new(0);
+		drop(rcu.watch());
+
+		rcu.update(1);
+		assert!(rcu.watchers.lock().senders.is_empty());
+		assert_eq!(rcu.watchers.count.load(Ordering::Relaxed), 0);
+	}
+
+	#[test]
+	fn test_wait_changed_unregisters() {
+		let rcu = Rcu::new(0);
+
+		for i in 1..=10 {
+			let old = rcu.get();
+			rcu.update(i);
+			drop(rcu.wait_changed(&old));
+		}
+
+		assert!(rcu.watchers.lock().senders.is_empty());
+	}
+
+	#[test]
+	fn test_watch_threads() {
+		let rcu = Rcu::new(0);
+		let ready = Barrier::new(2);
+
+		scope(|scope| {
+			scope.spawn(|| {
+				let watch = rcu.watch();
+				ready.wait();
+
+				for v in watch {
+					if *v == 10 {
+						break;
+					}
+				}
+			});
+
+			ready.wait();
+			for i in 1..=10 {
+				rcu.update(i);
+			}
+		});
+	}
+}
@@ -0,0 +1,156 @@
+use core::fmt;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::rcu::{Guard, OldGuard, Rcu};
+use crate::reclaim::{Reclaim, RefCounting};
+
+/// An [`Rcu`] whose updates are serialized by a lock.
+///
+/// With a plain [`Rcu`], concurrent [`update`]s race and all but the last
+/// one are lost, while [`update_with`] might have to call its closure many
+/// times. Here, writers take turns on a [`Mutex`] instead, so every update
+/// sees the value written by the previous one and closures run exactly
+/// once. Readers never touch the lock, so [`get`] stays lock-free.
+///
+/// This is the recommended type when there is more than one writer.
+///
+/// [`update`]: Rcu::update
+/// [`update_with`]: Rcu::update_with
+/// [`get`]: Self::get
+pub struct WriteLockRcu<T, R: Reclaim = RefCounting> {
+	rcu: Rcu<T, R>,
+	lock: Mutex<()>,
+}
+
+impl<T> WriteLockRcu<T> {
+	/// Create a new [`WriteLockRcu`] with an initial value of `data`.
+	pub fn new(data: T) -> Self {
+		Self::from(Rcu::new(data))
+	}
+}
+
+impl<T, R: Reclaim> WriteLockRcu<T, R> {
+	/// Get the value inside the [`Rcu`].
+	///
+	/// This function does _not_ take the lock.
+	///
+	/// See: [`Rcu::get`].
+	pub fn get(&self) -> Guard<'_, T> {
+		self.rcu.get()
+	}
+
+	/// Update the value inside the [`Rcu`].
+	///
+	/// This function blocks execution while another writer holds the lock.
+	///
+	/// See: [`Rcu::update`].
+	pub fn update(&self, new: T) {
+		drop(self.replace(new));
+	}
+
+	/// Update the value inside the [`Rcu`] and return a guard to the old
+	/// one.
+	///
+	/// This function blocks execution while another writer holds the lock.
+	///
+	/// See: [`Rcu::replace`].
+	pub fn replace(&self, new: T) -> OldGuard<'_, T> {
+		let _lock = self.lock();
+		self.rcu.replace(new)
+	}
+
+	/// Update the value inside the [`Rcu`] to the one `f` returns for the
+	/// current one.
+	///
+	/// `f` is called exactly once, with the lock held. No other writer can
+	/// replace the value in the meantime. If `f` panics, the [`Rcu`] is
+	/// left unmodified.
+	///
+	/// This function blocks execution while another writer holds the lock.
+	pub fn update_with<F>(&self, f: F)
+	where
+		F: FnOnce(&T) -> T,
+	{
+		let _lock = self.lock();
+		let new = f(&self.rcu.get());
+		self.rcu.update(new);
+	}
+
+	/// Get the value inside the [`Rcu`] along with its version.
+	///
+	/// This function does _not_ take the lock.
+	///
+	/// See: [`Rcu::get_version`].
+	pub fn get_version(&self) -> (Guard<'_, T>, u64) {
+		self.rcu.get_version()
+	}
+
+	/// Get the number of times the value has been replaced.
+	///
+	/// This function does _not_ take the lock.
+	///
+	/// See: [`Rcu::generation`].
+	pub fn generation(&self) -> u64 {
+		self.rcu.generation()
+	}
+
+	/// Take the [`Rcu`] out of the wrapper.
+	pub fn into_rcu(self) -> Rcu<T, R> {
+		self.rcu
+	}
+
+	fn lock(&self) -> MutexGuard<'_, ()> {
+		// The lock protects no data, and the `Rcu` is left unmodified if
+		// a writer panics, so poisoning can be ignored.
+		self.lock.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+impl<T, R: Reclaim> From<Rcu<T, R>> for WriteLockRcu<T, R> {
+	fn from(rcu: Rcu<T, R>) -> Self {
+		Self { rcu, lock: Mutex::new(()) }
+	}
+}
+
+impl<T: Default, R: Reclaim + Default> Default
+	for WriteLockRcu<T, R>
+{
+	fn default() -> Self {
+		Self::from(Rcu::default())
+	}
+}
+
+impl<T: fmt::Debug, R: Reclaim> fmt::Debug for WriteLockRcu<T, R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&self.rcu, f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::thread::scope;
+
+	#[test]
+	fn test_write_lock() {
+		const WRITERS: usize = 4;
+		const UPDATES: usize = 1000;
+
+		let rcu = WriteLockRcu::new(0);
+
+		scope(|scope| {
+			for _ in 0..WRITERS {
+				scope.spawn(|| {
+					for _ in 0..UPDATES {
+						rcu.update_with(|x| x + 1);
+					}
+				});
+			}
+		});
+
+		assert_eq!(*rcu.get(), WRITERS * UPDATES);
+		assert_eq!(rcu.generation(), (WRITERS * UPDATES) as u64);
+	}
+}